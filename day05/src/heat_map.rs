@@ -0,0 +1,130 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    raster::{covers, rasterize, Rasterization},
+    Line, Point,
+};
+
+pub struct HeatMap {
+    lines: Vec<Line>,
+    rasterization: Rasterization,
+    density: BTreeMap<(i64, i64), usize>,
+}
+
+impl HeatMap {
+    pub fn new(lines: Vec<Line>, rasterization: Rasterization) -> Self {
+        let mut density = BTreeMap::new();
+
+        for line in lines.iter() {
+            for point in rasterize(line, rasterization) {
+                *density.entry(point).or_insert(0) += 1;
+            }
+        }
+
+        HeatMap {
+            lines,
+            rasterization,
+            density,
+        }
+    }
+
+    pub fn covering(&self, point: &Point) -> Vec<&Line> {
+        self.lines
+            .iter()
+            .filter(|l| covers(l, (point.x, point.y), self.rasterization))
+            .collect()
+    }
+
+    pub fn overlaps(&self, k: usize) -> Vec<Point> {
+        self.density
+            .iter()
+            .filter(|(_, count)| **count >= k)
+            .map(|((x, y), _)| Point { x: *x, y: *y })
+            .collect()
+    }
+
+    pub fn max_density(&self) -> usize {
+        self.density.values().max().copied().unwrap_or(0)
+    }
+
+    pub fn to_ascii(&self) -> String {
+        self.rows()
+            .map(|row| {
+                row.iter()
+                    .map(|count| match count {
+                        0 => '.',
+                        c if *c < 10 => char::from_digit(*c as u32, 10).unwrap(),
+                        _ => '#',
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    pub fn to_pgm(&self) -> Vec<u8> {
+        let max_count = self.max_density().max(1);
+        let mut output = self.get_header("P5");
+
+        for row in self.rows() {
+            output.extend(row.iter().map(|c| (c * 255 / max_count) as u8));
+        }
+
+        output
+    }
+
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let max_count = self.max_density().max(1);
+        let mut output = self.get_header("P6");
+
+        for row in self.rows() {
+            output.extend(row.iter().flat_map(|c| get_heat_color(*c, max_count)));
+        }
+
+        output
+    }
+
+    fn get_header(&self, magic: &str) -> Vec<u8> {
+        let (width, height) = if self.density.is_empty() {
+            (0, 0)
+        } else {
+            let ((min_x, min_y), (max_x, max_y)) = self.bounds();
+            (max_x - min_x + 1, max_y - min_y + 1)
+        };
+
+        format!("{}\n{} {}\n255\n", magic, width, height).into_bytes()
+    }
+
+    fn bounds(&self) -> ((i64, i64), (i64, i64)) {
+        self.density.keys().fold(
+            ((i64::MAX, i64::MAX), (i64::MIN, i64::MIN)),
+            |((min_x, min_y), (max_x, max_y)), (x, y)| {
+                (
+                    (min_x.min(*x), min_y.min(*y)),
+                    (max_x.max(*x), max_y.max(*y)),
+                )
+            },
+        )
+    }
+
+    fn rows(&self) -> impl Iterator<Item = Vec<usize>> + '_ {
+        let ((min_x, min_y), (max_x, max_y)) = self.bounds();
+
+        (min_y..=max_y).map(move |y| {
+            (min_x..=max_x)
+                .map(|x| self.density.get(&(x, y)).copied().unwrap_or(0))
+                .collect()
+        })
+    }
+}
+
+// black through red and yellow to white
+fn get_heat_color(count: usize, max_count: usize) -> [u8; 3] {
+    let heat = count * 765 / max_count;
+
+    [
+        heat.min(255) as u8,
+        heat.saturating_sub(255).min(255) as u8,
+        heat.saturating_sub(510).min(255) as u8,
+    ]
+}
@@ -1,79 +1,72 @@
+mod heat_map;
+mod raster;
+mod sweep;
+
 use lazy_static::lazy_static;
 use regex::{Match, Regex};
-use std::{collections::BTreeMap, fs};
+use std::{env, fs};
+
+use heat_map::HeatMap;
+use raster::Rasterization;
+use sweep::count_overlaps;
 
 fn main() {
     let input = fs::read_to_string("./_data/input.txt").expect("oh noes");
+    let args: Vec<String> = env::args().collect();
+    let rasterization = args
+        .get(1)
+        .map(|a| a.parse::<Rasterization>().unwrap())
+        .unwrap_or(Rasterization::Lattice);
+
+    if let Some(path) = args.get(2) {
+        render(input.clone(), rasterization, path);
+    }
 
     let output = process_data(input.clone());
-    let adv_output = process_data_adv(input);
+    let adv_output = process_data_adv(input, rasterization);
 
     println!("Result is: {}", output);
     println!("Adv result is: {}", adv_output);
 }
 
-fn process_data(input: String) -> String {
-    let mut vents: BTreeMap<(u32, u32), u32> = BTreeMap::new();
+fn render(input: String, rasterization: Rasterization, path: &str) {
+    let heat_map = HeatMap::new(parse(input), rasterization);
 
-    for line in parse(input)
-        .iter()
-        .filter(|line| line.start.x == line.end.x || line.start.y == line.end.y)
-    {
-        if line.start.x == line.end.x {
-            let x = line.start.x;
+    let content = match path.rsplit('.').next() {
+        Some("pgm") => heat_map.to_pgm(),
+        Some("ppm") => heat_map.to_ppm(),
+        _ => heat_map.to_ascii().into_bytes(),
+    };
 
-            for y in get_range(line.start.y, line.end.y) {
-                let vent = vents.entry((x, y)).or_insert(0);
-                *vent += 1;
-            }
-        } else {
-            let y = line.start.y;
+    fs::write(path, content).expect("oh noes");
 
-            for x in get_range(line.start.x, line.end.x) {
-                let vent = vents.entry((x, y)).or_insert(0);
-                *vent += 1;
-            }
+    let max_density = heat_map.max_density();
+
+    for point in heat_map.overlaps(max_density.max(1)) {
+        println!("Hottest point {:?} is covered by:", point);
+
+        for line in heat_map.covering(&point) {
+            println!("  {:?}", line);
         }
     }
-
-    vents.into_iter().filter(|v| v.1 > 1).count().to_string()
 }
 
-fn process_data_adv(input: String) -> String {
-    let mut vents: BTreeMap<(u32, u32), u32> = BTreeMap::new();
-
-    for line in parse(input).iter() {
-        if line.start.x == line.end.x {
-            let x = line.start.x;
-
-            for y in get_range(line.start.y, line.end.y) {
-                let vent = vents.entry((x, y)).or_insert(0);
-                *vent += 1;
-            }
-        } else if line.start.y == line.end.y {
-            let y = line.start.y;
-
-            for x in get_range(line.start.x, line.end.x) {
-                let vent = vents.entry((x, y)).or_insert(0);
-                *vent += 1;
-            }
-        } else {
-            let range_x = get_range(line.start.x, line.end.x);
-            let range_y = get_range(line.start.y, line.end.y);
+fn process_data(input: String) -> String {
+    let lines: Vec<Line> = parse(input)
+        .into_iter()
+        .filter(|line| line.start.x == line.end.x || line.start.y == line.end.y)
+        .collect();
 
-            for (x, y) in range_x.iter().zip(range_y) {
-                let vent = vents.entry((*x, y)).or_insert(0);
-                *vent += 1;
-            }
-        }
-    }
+    count_overlaps(&lines, Rasterization::Lattice).to_string()
+}
 
-    vents.into_iter().filter(|v| v.1 > 1).count().to_string()
+fn process_data_adv(input: String, rasterization: Rasterization) -> String {
+    count_overlaps(&parse(input), rasterization).to_string()
 }
 
 fn parse(input: String) -> Vec<Line> {
     lazy_static! {
-        static ref LINE_RE: Regex = Regex::new("(\\d+),(\\d+) -> (\\d+),(\\d+)").unwrap();
+        static ref LINE_RE: Regex = Regex::new("(-?\\d+),(-?\\d+) -> (-?\\d+),(-?\\d+)").unwrap();
     }
 
     input
@@ -97,22 +90,14 @@ fn parse(input: String) -> Vec<Line> {
         .collect()
 }
 
-fn parse_capture(mat: Option<Match>) -> Option<u32> {
-    mat.map(|m| m.as_str()).and_then(|s| s.parse::<u32>().ok())
-}
-
-fn get_range(start: u32, end: u32) -> Vec<u32> {
-    if start < end {
-        (start..=end).collect()
-    } else {
-        (end..=start).rev().collect()
-    }
+fn parse_capture(mat: Option<Match>) -> Option<i64> {
+    mat.map(|m| m.as_str()).and_then(|s| s.parse::<i64>().ok())
 }
 
 #[derive(Debug, Default, PartialEq, Eq)]
 struct Point {
-    x: u32,
-    y: u32,
+    x: i64,
+    y: i64,
 }
 
 #[derive(Debug, Default, PartialEq, Eq)]
@@ -123,7 +108,7 @@ struct Line {
 
 #[cfg(test)]
 mod tests {
-    use crate::*;
+    use crate::{heat_map::HeatMap, raster::rasterize, *};
 
     const TEST_CASE: &str = "0,9 -> 5,9
     8,0 -> 0,8
@@ -147,7 +132,7 @@ mod tests {
             end: Point { x: 5, y: 9 },
         };
 
-        assert_eq!(test_line, *lines.get(0).unwrap());
+        assert_eq!(test_line, *lines.first().unwrap());
     }
 
     #[test]
@@ -157,6 +142,196 @@ mod tests {
 
     #[test]
     fn adv_check() {
-        assert_eq!("12", process_data_adv(TEST_CASE.to_string()));
+        assert_eq!(
+            "12",
+            process_data_adv(TEST_CASE.to_string(), Rasterization::Lattice)
+        );
+    }
+
+    #[test]
+    fn huge_coordinates_check() {
+        let input = "0,1000000 -> 4000000,1000000
+        1000000,0 -> 1000000,4000000
+        0,0 -> 3000000,3000000
+        3000000,0 -> 0,3000000
+        3000000,1000000 -> 1000000,1000000";
+
+        assert_eq!(
+            "2000003",
+            process_data_adv(input.to_string(), Rasterization::Lattice)
+        );
+    }
+
+    #[test]
+    fn random_lines_check() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(5);
+
+        for _ in 0..50 {
+            let input = (0..30)
+                .map(|_| {
+                    let (x, y) = (rng.gen_range(0..40), rng.gen_range(40..80));
+                    let len = rng.gen_range(0..40);
+                    let end = match rng.gen_range(0..4) {
+                        0 => (x + len, y),
+                        1 => (x, y + len),
+                        2 => (x + len, y + len),
+                        _ => (x + len, y - len),
+                    };
+
+                    if rng.gen() {
+                        format!("{},{} -> {},{}", x, y, end.0, end.1)
+                    } else {
+                        format!("{},{} -> {},{}", end.0, end.1, x, y)
+                    }
+                })
+                .collect::<Vec<String>>()
+                .join("\n");
+
+            assert_eq!(
+                count_by_points(&parse(input.clone())),
+                count_overlaps(&parse(input), Rasterization::Lattice)
+            );
+        }
+    }
+
+    #[test]
+    fn negative_coordinates_check() {
+        let input = "-3,-3 -> 3,3
+        -3,3 -> 3,-3
+        -5,0 -> 5,0";
+
+        assert_eq!(
+            Point { x: -3, y: -3 },
+            parse(input.to_string()).first().unwrap().start
+        );
+        assert_eq!("0", process_data(input.to_string()));
+        assert_eq!(
+            "1",
+            process_data_adv(input.to_string(), Rasterization::Lattice)
+        );
+    }
+
+    #[test]
+    fn sloped_lines_check() {
+        let input = "0,0 -> 6,3
+        0,3 -> 6,0
+        2,0 -> 2,4
+        0,1 -> 6,1";
+
+        assert_eq!(
+            "3",
+            process_data_adv(input.to_string(), Rasterization::Lattice)
+        );
+        assert_eq!(
+            "5",
+            process_data_adv(input.to_string(), Rasterization::Bresenham)
+        );
+    }
+
+    #[test]
+    fn rasterization_check() {
+        let line = Line {
+            start: Point { x: 0, y: 0 },
+            end: Point { x: 6, y: 3 },
+        };
+
+        assert_eq!(
+            vec![(0, 0), (2, 1), (4, 2), (6, 3)],
+            rasterize(&line, Rasterization::Lattice)
+        );
+        assert_eq!(
+            vec![(0, 0), (1, 1), (2, 1), (3, 2), (4, 2), (5, 3), (6, 3)],
+            rasterize(&line, Rasterization::Bresenham)
+        );
+    }
+
+    #[test]
+    fn diagonal_rasterization_check() {
+        for line in parse(TEST_CASE.to_string()) {
+            assert_eq!(
+                rasterize(&line, Rasterization::Lattice),
+                rasterize(&line, Rasterization::Bresenham)
+            );
+        }
+
+        assert_eq!(
+            "12",
+            process_data_adv(TEST_CASE.to_string(), Rasterization::Bresenham)
+        );
+    }
+
+    #[test]
+    fn ascii_render_check() {
+        let heat_map = HeatMap::new(parse(TEST_CASE.to_string()), Rasterization::Lattice);
+
+        let expected = "1.1....11.
+.111...2..
+..2.1.111.
+...1.2.2..
+.112313211
+...1.2....
+..1...1...
+.1.....1..
+1.......1.
+222111....";
+
+        assert_eq!(expected, heat_map.to_ascii());
+    }
+
+    #[test]
+    fn image_render_check() {
+        let heat_map = HeatMap::new(parse(TEST_CASE.to_string()), Rasterization::Lattice);
+
+        let pgm = heat_map.to_pgm();
+        let ppm = heat_map.to_ppm();
+
+        assert!(pgm.starts_with(b"P5\n10 10\n255\n"));
+        assert_eq!(13 + 100, pgm.len());
+        assert_eq!(255, pgm[13 + 4 * 10 + 4]);
+        assert!(ppm.starts_with(b"P6\n10 10\n255\n"));
+        assert_eq!(13 + 300, ppm.len());
+        assert_eq!([0, 0, 0], ppm[14..17]);
+    }
+
+    #[test]
+    fn query_check() {
+        let lines = parse(TEST_CASE.to_string());
+        let heat_map = HeatMap::new(parse(TEST_CASE.to_string()), Rasterization::Lattice);
+
+        assert_eq!(12, heat_map.overlaps(2).len());
+        assert_eq!(
+            vec![Point { x: 4, y: 4 }, Point { x: 6, y: 4 }],
+            heat_map.overlaps(3)
+        );
+        assert_eq!(
+            vec![&lines[2], &lines[4]],
+            heat_map.covering(&Point { x: 7, y: 4 })
+        );
+        assert!(heat_map.covering(&Point { x: 9, y: 9 }).is_empty());
+    }
+
+    fn count_by_points(lines: &[Line]) -> usize {
+        let mut vents = std::collections::BTreeMap::new();
+
+        for line in lines {
+            let dx = (line.end.x - line.start.x).signum();
+            let dy = (line.end.y - line.start.y).signum();
+            let (mut x, mut y) = (line.start.x, line.start.y);
+
+            loop {
+                *vents.entry((x, y)).or_insert(0) += 1;
+
+                if (x, y) == (line.end.x, line.end.y) {
+                    break;
+                }
+
+                x += dx;
+                y += dy;
+            }
+        }
+
+        vents.values().filter(|v| **v > 1).count()
     }
 }
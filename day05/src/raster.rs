@@ -0,0 +1,112 @@
+use std::str::FromStr;
+
+use crate::Line;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rasterization {
+    Bresenham,
+    Lattice,
+}
+
+impl FromStr for Rasterization {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bresenham" => Ok(Rasterization::Bresenham),
+            "lattice" => Ok(Rasterization::Lattice),
+            x => Err(format!("Unknown rasterization: {}", x)),
+        }
+    }
+}
+
+pub fn rasterize(line: &Line, rasterization: Rasterization) -> Vec<(i64, i64)> {
+    match rasterization {
+        Rasterization::Bresenham => bresenham(line),
+        Rasterization::Lattice => lattice(line),
+    }
+}
+
+pub fn covers(line: &Line, (x, y): (i64, i64), rasterization: Rasterization) -> bool {
+    let (min_x, max_x) = (line.start.x.min(line.end.x), line.start.x.max(line.end.x));
+    let (min_y, max_y) = (line.start.y.min(line.end.y), line.start.y.max(line.end.y));
+
+    if x < min_x || x > max_x || y < min_y || y > max_y {
+        return false;
+    }
+
+    match rasterization {
+        Rasterization::Bresenham => bresenham(line).contains(&(x, y)),
+        Rasterization::Lattice => {
+            let dx = line.end.x - line.start.x;
+            let dy = line.end.y - line.start.y;
+            let steps = gcd(dx.abs(), dy.abs()).max(1);
+
+            dx * (y - line.start.y) == dy * (x - line.start.x)
+                && (x - line.start.x) % (dx / steps).abs().max(1) == 0
+                && (y - line.start.y) % (dy / steps).abs().max(1) == 0
+        }
+    }
+}
+
+fn bresenham(line: &Line) -> Vec<(i64, i64)> {
+    let dx = (line.end.x - line.start.x).abs();
+    let dy = -(line.end.y - line.start.y).abs();
+    let sx = (line.end.x - line.start.x).signum();
+    let sy = (line.end.y - line.start.y).signum();
+
+    let mut points = Vec::with_capacity((dx - dy + 1) as usize);
+    let (mut x, mut y) = (line.start.x, line.start.y);
+    let mut error = dx + dy;
+
+    loop {
+        points.push((x, y));
+
+        if x == line.end.x && y == line.end.y {
+            break;
+        }
+
+        let doubled = 2 * error;
+
+        if doubled >= dy {
+            error += dy;
+            x += sx;
+        }
+
+        if doubled <= dx {
+            error += dx;
+            y += sy;
+        }
+    }
+
+    points
+}
+
+// only the points lying exactly on the line, which are evenly spaced
+// by the line vector divided by gcd of its components
+fn lattice(line: &Line) -> Vec<(i64, i64)> {
+    let dx = line.end.x - line.start.x;
+    let dy = line.end.y - line.start.y;
+    let steps = gcd(dx.abs(), dy.abs());
+
+    if steps == 0 {
+        return vec![(line.start.x, line.start.y)];
+    }
+
+    (0..=steps)
+        .map(|i| {
+            (
+                line.start.x + i * (dx / steps),
+                line.start.y + i * (dy / steps),
+            )
+        })
+        .collect()
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
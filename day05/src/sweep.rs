@@ -0,0 +1,354 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    raster::{rasterize, Rasterization},
+    Line,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Horizontal,
+    Vertical,
+    Diagonal,
+    AntiDiagonal,
+}
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Horizontal,
+    Direction::Vertical,
+    Direction::Diagonal,
+    Direction::AntiDiagonal,
+];
+
+impl Direction {
+    pub fn of(line: &Line) -> Option<Self> {
+        let dx = line.end.x - line.start.x;
+        let dy = line.end.y - line.start.y;
+
+        if dy == 0 {
+            Some(Direction::Horizontal)
+        } else if dx == 0 {
+            Some(Direction::Vertical)
+        } else if dx == dy {
+            Some(Direction::Diagonal)
+        } else if dx == -dy {
+            Some(Direction::AntiDiagonal)
+        } else {
+            None
+        }
+    }
+
+    // every line of a given direction is identified by a key
+    // and its points by a parameter along that line
+    fn key(&self, (x, y): (i64, i64)) -> i64 {
+        match self {
+            Direction::Horizontal => y,
+            Direction::Vertical => x,
+            Direction::Diagonal => x - y,
+            Direction::AntiDiagonal => x + y,
+        }
+    }
+
+    fn param(&self, (x, y): (i64, i64)) -> i64 {
+        match self {
+            Direction::Vertical => y,
+            _ => x,
+        }
+    }
+
+    fn point(&self, key: i64, t: i64) -> (i64, i64) {
+        match self {
+            Direction::Horizontal => (t, key),
+            Direction::Vertical => (key, t),
+            Direction::Diagonal => (t, t - key),
+            Direction::AntiDiagonal => (t, key - t),
+        }
+    }
+}
+
+type Intervals = BTreeMap<i64, Vec<(i64, i64)>>;
+
+struct Coverage {
+    covered: Intervals,
+    overlapped: Intervals,
+}
+
+// Straight and 45° lines are counted by the sweep without visiting their points,
+// lines of any other slope are rasterized and checked against the sweep.
+pub fn count_overlaps(lines: &[Line], rasterization: Rasterization) -> usize {
+    let (straight, sloped): (Vec<&Line>, Vec<&Line>) =
+        lines.iter().partition(|l| Direction::of(l).is_some());
+
+    let sweep = Sweep::new(&straight);
+
+    let mut rasterized: BTreeMap<(i64, i64), usize> = BTreeMap::new();
+
+    for line in sloped {
+        for point in rasterize(line, rasterization) {
+            *rasterized.entry(point).or_insert(0) += 1;
+        }
+    }
+
+    sweep.count()
+        + rasterized
+            .into_iter()
+            .filter(|(p, count)| {
+                let depth = sweep.depth(*p);
+                depth < 2 && depth + count >= 2
+            })
+            .count()
+}
+
+pub struct Sweep {
+    coverages: Vec<Coverage>,
+    crossings: BTreeSet<(i64, i64)>,
+}
+
+impl Sweep {
+    pub fn new(lines: &[&Line]) -> Self {
+        let coverages: Vec<Coverage> = DIRECTIONS
+            .iter()
+            .map(|d| {
+                let group: Vec<&Line> = lines
+                    .iter()
+                    .filter(|l| Direction::of(l) == Some(*d))
+                    .copied()
+                    .collect();
+
+                get_coverage(d, &group)
+            })
+            .collect();
+
+        let mut crossings = BTreeSet::new();
+
+        for a in 0..DIRECTIONS.len() {
+            for b in (a + 1)..DIRECTIONS.len() {
+                crossings.extend(find_crossings(
+                    &DIRECTIONS[a],
+                    &coverages[a].covered,
+                    &DIRECTIONS[b],
+                    &coverages[b].covered,
+                ));
+            }
+        }
+
+        Sweep {
+            coverages,
+            crossings,
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        // collinear overlaps of different directions can only meet in crossings,
+        // so it is enough to not count those points twice
+        let overlapped = self
+            .coverages
+            .iter()
+            .flat_map(|c| c.overlapped.values())
+            .flatten()
+            .map(|(lo, hi)| (hi - lo + 1) as usize)
+            .sum::<usize>();
+
+        let shared = self
+            .crossings
+            .iter()
+            .map(|p| {
+                DIRECTIONS
+                    .iter()
+                    .zip(self.coverages.iter())
+                    .filter(|(d, c)| contains(&c.overlapped, d.key(*p), d.param(*p)))
+                    .count()
+            })
+            .sum::<usize>();
+
+        overlapped + self.crossings.len() - shared
+    }
+
+    // number of lines covering the point, capped at two per direction
+    pub fn depth(&self, point: (i64, i64)) -> usize {
+        DIRECTIONS
+            .iter()
+            .zip(self.coverages.iter())
+            .map(|(d, c)| {
+                let (key, t) = (d.key(point), d.param(point));
+
+                if contains(&c.overlapped, key, t) {
+                    2
+                } else if contains(&c.covered, key, t) {
+                    1
+                } else {
+                    0
+                }
+            })
+            .sum()
+    }
+}
+
+fn get_coverage(direction: &Direction, lines: &[&Line]) -> Coverage {
+    let mut events: BTreeMap<i64, Vec<(i64, i64)>> = BTreeMap::new();
+
+    for line in lines {
+        let start = (line.start.x, line.start.y);
+        let end = (line.end.x, line.end.y);
+        let (lo, hi) = min_max(direction.param(start), direction.param(end));
+
+        let line_events = events.entry(direction.key(start)).or_default();
+        line_events.push((lo, 1));
+        line_events.push((hi + 1, -1));
+    }
+
+    let mut covered = Intervals::new();
+    let mut overlapped = Intervals::new();
+
+    for (key, mut line_events) in events {
+        line_events.sort_unstable();
+
+        let mut depth = 0;
+        let mut covered_from = 0;
+        let mut overlapped_from = 0;
+
+        for (t, change) in line_events {
+            let previous = depth;
+            depth += change;
+
+            if previous < 1 && depth >= 1 {
+                covered_from = t;
+            } else if previous >= 1 && depth < 1 {
+                push_interval(&mut covered, key, covered_from, t - 1);
+            }
+
+            if previous < 2 && depth >= 2 {
+                overlapped_from = t;
+            } else if previous >= 2 && depth < 2 {
+                push_interval(&mut overlapped, key, overlapped_from, t - 1);
+            }
+        }
+    }
+
+    Coverage {
+        covered,
+        overlapped,
+    }
+}
+
+// same position events are sorted so that the interval boundaries
+// touch each other, merge them back together
+fn push_interval(intervals: &mut Intervals, key: i64, lo: i64, hi: i64) {
+    if lo > hi {
+        return;
+    }
+
+    let line_intervals = intervals.entry(key).or_default();
+
+    match line_intervals.last_mut() {
+        Some(last) if last.1 + 1 >= lo => last.1 = last.1.max(hi),
+        _ => line_intervals.push((lo, hi)),
+    }
+}
+
+fn contains(intervals: &Intervals, key: i64, t: i64) -> bool {
+    intervals
+        .get(&key)
+        .map(|v| {
+            let index = v.partition_point(|(_, hi)| *hi < t);
+            index < v.len() && v[index].0 <= t
+        })
+        .unwrap_or(false)
+}
+
+// Lines of two different directions are mapped into a space where the first
+// direction becomes horizontal and the second one vertical, then all crossings
+// are found by sweeping over the vertical lines with a set of active rows.
+fn find_crossings(
+    first: &Direction,
+    first_intervals: &Intervals,
+    second: &Direction,
+    second_intervals: &Intervals,
+) -> Vec<(i64, i64)> {
+    let (to_sweep, from_sweep) = get_sweep_transform(first, second);
+
+    let map_interval = |d: &Direction, key: i64, (lo, hi): (i64, i64)| {
+        (to_sweep(d.point(key, lo)), to_sweep(d.point(key, hi)))
+    };
+
+    let mut events = Vec::new();
+
+    for (key, intervals) in first_intervals {
+        for interval in intervals {
+            let ((u1, v), (u2, _)) = map_interval(first, *key, *interval);
+            let (lo, hi) = min_max(u1, u2);
+
+            events.push((lo, 0, v, v));
+            events.push((hi, 2, v, v));
+        }
+    }
+
+    for (key, intervals) in second_intervals {
+        for interval in intervals {
+            let ((u, v1), (_, v2)) = map_interval(second, *key, *interval);
+            let (lo, hi) = min_max(v1, v2);
+
+            events.push((u, 1, lo, hi));
+        }
+    }
+
+    events.sort_unstable();
+
+    let mut active_rows = BTreeSet::new();
+    let mut crossings = Vec::new();
+
+    for (u, kind, lo, hi) in events {
+        match kind {
+            0 => {
+                active_rows.insert(lo);
+            }
+            1 => {
+                for v in active_rows.range(lo..=hi) {
+                    if let Some(p) = from_sweep((u, *v)) {
+                        crossings.push(p);
+                    }
+                }
+            }
+            _ => {
+                active_rows.remove(&lo);
+            }
+        }
+    }
+
+    crossings
+}
+
+type Transform = fn((i64, i64)) -> (i64, i64);
+type InverseTransform = fn((i64, i64)) -> Option<(i64, i64)>;
+
+fn get_sweep_transform(first: &Direction, second: &Direction) -> (Transform, InverseTransform) {
+    use Direction::*;
+
+    match (first, second) {
+        (Horizontal, Vertical) => (|(x, y)| (x, y), |(u, v)| Some((u, v))),
+        (Horizontal, Diagonal) => (|(x, y)| (x - y, y), |(u, v)| Some((u + v, v))),
+        (Horizontal, AntiDiagonal) => (|(x, y)| (x + y, y), |(u, v)| Some((u - v, v))),
+        (Vertical, Diagonal) => (|(x, y)| (y - x, x), |(u, v)| Some((v, u + v))),
+        (Vertical, AntiDiagonal) => (|(x, y)| (x + y, x), |(u, v)| Some((v, u - v))),
+        // diagonals only cross on a lattice point when their keys share parity
+        (Diagonal, AntiDiagonal) => (
+            |(x, y)| (x + y, x - y),
+            |(u, v)| {
+                if (u + v) % 2 == 0 {
+                    Some(((u + v) / 2, (u - v) / 2))
+                } else {
+                    None
+                }
+            },
+        ),
+        (a, b) => panic!("Unexpected direction pair: {:?}, {:?}", a, b),
+    }
+}
+
+fn min_max(a: i64, b: i64) -> (i64, i64) {
+    if a < b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
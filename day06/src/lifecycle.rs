@@ -0,0 +1,251 @@
+use std::{fmt, num::NonZeroU64};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arithmetic {
+    Checked,
+    Modulo(NonZeroU64),
+}
+
+impl Arithmetic {
+    fn add(&self, a: u128, b: u128) -> Option<u128> {
+        match self {
+            Arithmetic::Checked => a.checked_add(b),
+            Arithmetic::Modulo(m) => Some((a + b) % m.get() as u128),
+        }
+    }
+
+    fn mul(&self, a: u128, b: u128) -> Option<u128> {
+        match self {
+            Arithmetic::Checked => a.checked_mul(b),
+            Arithmetic::Modulo(m) => Some((a * b) % m.get() as u128),
+        }
+    }
+}
+
+type Matrix = Vec<Vec<u128>>;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum LifecycleError {
+    SpawnPeriod,
+    Timer { timer: usize, max: usize },
+    Overflow,
+}
+
+impl fmt::Display for LifecycleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LifecycleError::SpawnPeriod => write!(f, "Spawn period has to be at least a day"),
+            LifecycleError::Timer { timer, max } => {
+                write!(f, "Timer {} is above the newborn timer {}", timer, max)
+            }
+            LifecycleError::Overflow => write!(f, "Population overflow"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lifecycle {
+    pub spawn_period: usize,
+    pub first_delay: usize,
+    pub max_spawns: Option<usize>,
+}
+
+impl Default for Lifecycle {
+    fn default() -> Self {
+        Lifecycle {
+            spawn_period: 7,
+            first_delay: 2,
+            max_spawns: None,
+        }
+    }
+}
+
+impl Lifecycle {
+    pub fn population(
+        &self,
+        timers: &[usize],
+        days: u64,
+        arithmetic: Arithmetic,
+    ) -> Result<u128, LifecycleError> {
+        self.validate()?;
+
+        let initial = self.get_initial_state(timers)?;
+        let transition =
+            matrix_pow(&self.get_transition(), days, arithmetic).ok_or(LifecycleError::Overflow)?;
+
+        multiply(&transition, &initial, arithmetic)
+            .and_then(|state| {
+                state
+                    .into_iter()
+                    .try_fold(0, |acc, count| arithmetic.add(acc, count))
+            })
+            .ok_or(LifecycleError::Overflow)
+    }
+
+    pub fn series(
+        &self,
+        timers: &[usize],
+        days: u64,
+        arithmetic: Arithmetic,
+    ) -> Result<Vec<u128>, LifecycleError> {
+        self.validate()?;
+
+        let transition = self.get_transition();
+        let mut state = self.get_initial_state(timers)?;
+        let mut output = Vec::with_capacity(days as usize + 1);
+
+        for day in 0..=days {
+            if day > 0 {
+                state =
+                    multiply(&transition, &state, arithmetic).ok_or(LifecycleError::Overflow)?;
+            }
+
+            output.push(
+                state
+                    .iter()
+                    .try_fold(0, |acc, count| arithmetic.add(acc, *count))
+                    .ok_or(LifecycleError::Overflow)?,
+            );
+        }
+
+        Ok(output)
+    }
+
+    // the fields are public, so the model is checked before every use
+    fn validate(&self) -> Result<(), LifecycleError> {
+        if self.spawn_period == 0 {
+            return Err(LifecycleError::SpawnPeriod);
+        }
+
+        Ok(())
+    }
+
+    // State holds counts of fish by their timer. Newborn fish start with the
+    // first cycle timer and have not spawned yet; with mortality, adults are
+    // further split by how many times they have already spawned.
+    fn get_size(&self) -> usize {
+        self.get_newborn_timer() + 1 + self.spawn_period * (self.get_generations() - 1)
+    }
+
+    fn get_newborn_timer(&self) -> usize {
+        self.spawn_period + self.first_delay - 1
+    }
+
+    fn get_generations(&self) -> usize {
+        self.max_spawns.unwrap_or(1).max(1)
+    }
+
+    fn get_index(&self, timer: usize, spawns: usize) -> usize {
+        if spawns == 0 {
+            timer
+        } else {
+            self.get_newborn_timer() + 1 + (spawns - 1) * self.spawn_period + timer
+        }
+    }
+
+    // input fish have not spawned yet, so no timer can be above a newborn's
+    fn get_initial_state(&self, timers: &[usize]) -> Result<Vec<u128>, LifecycleError> {
+        let max = self.get_newborn_timer();
+        let mut state = vec![0; self.get_size()];
+
+        for &timer in timers {
+            if timer > max {
+                return Err(LifecycleError::Timer { timer, max });
+            }
+
+            state[self.get_index(timer, 0)] += 1;
+        }
+
+        Ok(state)
+    }
+
+    fn get_transition(&self) -> Matrix {
+        let size = self.get_size();
+        let mut transition = vec![vec![0; size]; size];
+
+        for spawns in 0..self.get_generations() {
+            let max_timer = if spawns == 0 {
+                self.get_newborn_timer()
+            } else {
+                self.spawn_period - 1
+            };
+
+            for timer in 1..=max_timer {
+                transition[self.get_index(timer - 1, spawns)][self.get_index(timer, spawns)] = 1;
+            }
+
+            // a fish allowed no spawns at all dies when its timer runs out
+            let from = self.get_index(0, spawns);
+            if self.max_spawns != Some(0) {
+                transition[self.get_index(self.get_newborn_timer(), 0)][from] += 1;
+            }
+
+            match self.max_spawns {
+                Some(m) if spawns + 1 >= m => {}
+                Some(_) => {
+                    transition[self.get_index(self.spawn_period - 1, spawns + 1)][from] += 1;
+                }
+                None => {
+                    transition[self.get_index(self.spawn_period - 1, 0)][from] += 1;
+                }
+            }
+        }
+
+        transition
+    }
+}
+
+fn multiply(matrix: &Matrix, vector: &[u128], arithmetic: Arithmetic) -> Option<Vec<u128>> {
+    matrix
+        .iter()
+        .map(|row| {
+            row.iter().zip(vector).try_fold(0, |acc, (a, b)| {
+                arithmetic.add(acc, arithmetic.mul(*a, *b)?)
+            })
+        })
+        .collect()
+}
+
+fn matrix_mul(a: &Matrix, b: &Matrix, arithmetic: Arithmetic) -> Option<Matrix> {
+    let size = a.len();
+    let mut output = vec![vec![0; size]; size];
+
+    for i in 0..size {
+        for k in 0..size {
+            if a[i][k] == 0 {
+                continue;
+            }
+
+            for j in 0..size {
+                output[i][j] = arithmetic.add(output[i][j], arithmetic.mul(a[i][k], b[k][j])?)?;
+            }
+        }
+    }
+
+    Some(output)
+}
+
+// with checked arithmetic an overflow can be reported even when only
+// the timers missing from the input would overflow
+fn matrix_pow(matrix: &Matrix, exponent: u64, arithmetic: Arithmetic) -> Option<Matrix> {
+    let size = matrix.len();
+    let mut output: Matrix = (0..size)
+        .map(|i| (0..size).map(|j| if i == j { 1 } else { 0 }).collect())
+        .collect();
+    let mut base = matrix.clone();
+    let mut rest = exponent;
+
+    while rest > 0 {
+        if rest & 1 == 1 {
+            output = matrix_mul(&output, &base, arithmetic)?;
+        }
+
+        rest >>= 1;
+
+        if rest > 0 {
+            base = matrix_mul(&base, &base, arithmetic)?;
+        }
+    }
+
+    Some(output)
+}
@@ -1,7 +1,37 @@
-use std::fs;
+mod lifecycle;
+
+use std::{env, fs, num::NonZeroU64};
+
+use lifecycle::{Arithmetic, Lifecycle};
 
 fn main() {
     let input = fs::read_to_string("./_data/input.txt").expect("oh noes");
+    let args: Vec<String> = env::args().collect();
+
+    match args.get(1).map(|a| &a[..]) {
+        Some("population") => {
+            let days = args[2].parse::<u64>().unwrap();
+            let arithmetic = args
+                .get(3)
+                .map(|m| {
+                    Arithmetic::Modulo(
+                        m.parse::<NonZeroU64>()
+                            .expect("Modulus must be a positive number"),
+                    )
+                })
+                .unwrap_or(Arithmetic::Checked);
+
+            match Lifecycle::default().population(&parse(input.clone()), days, arithmetic) {
+                Ok(count) => println!("Population after {} days is: {}", days, count),
+                Err(e) => println!("Population after {} days failed: {}", days, e),
+            }
+        }
+        Some("series") => {
+            let days = args[2].parse::<u64>().unwrap();
+            export_series(input.clone(), days, &args[3]);
+        }
+        _ => {}
+    }
 
     let output = process_data(input.clone());
     let adv_output = process_data_adv(input);
@@ -18,28 +48,38 @@ fn process_data_adv(input: String) -> String {
     grow(input, 256).to_string()
 }
 
-fn grow(input: String, days: u32) -> u64 {
-    let mut school = [0u64; 7];
-    let mut maturing = [0u64; 7];
+fn grow(input: String, days: u64) -> u128 {
+    Lifecycle::default()
+        .population(&parse(input), days, Arithmetic::Checked)
+        .unwrap_or_else(|e| panic!("{}", e))
+}
 
-    for t in input.trim().split(',') {
-        let i = t.parse::<usize>().unwrap();
-        school[i] += 1;
-    }
+fn export_series(input: String, days: u64, path: &str) {
+    let series = Lifecycle::default()
+        .series(&parse(input), days, Arithmetic::Checked)
+        .unwrap_or_else(|e| panic!("{}", e));
 
-    let mut r = 0usize;
+    let content = series
+        .iter()
+        .enumerate()
+        .map(|(day, count)| format!("{},{}", day, count))
+        .collect::<Vec<String>>()
+        .join("\n");
 
-    for _d in 0..(days + 2) {
-        maturing[(r + 2) % 7] = school[r];
-        school[r] += maturing[r];
-        r = (r + 1) % 7;
-    }
+    fs::write(path, format!("day,population\n{}\n", content)).expect("oh noes");
+}
 
-    school.iter().sum::<u64>()
+fn parse(input: String) -> Vec<usize> {
+    input
+        .trim()
+        .split(',')
+        .map(|t| t.parse::<usize>().unwrap())
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::lifecycle::LifecycleError;
     use crate::*;
 
     const TEST_CASE: &str = "3,4,3,1,2
@@ -54,4 +94,144 @@ mod tests {
     fn adv_check() {
         assert_eq!("26984457539", process_data_adv(TEST_CASE.to_string()));
     }
+
+    #[test]
+    fn huge_days_check() {
+        let timers = parse(TEST_CASE.to_string());
+        let lifecycle = Lifecycle::default();
+        let modulo = Arithmetic::Modulo(NonZeroU64::new(1_000_000_007).unwrap());
+
+        assert_eq!(
+            Ok(26984457539 % 1_000_000_007),
+            lifecycle.population(&timers, 256, modulo)
+        );
+        assert!(lifecycle
+            .population(&timers, 1_000_000_000_000, modulo)
+            .is_ok());
+        assert_eq!(
+            Err(LifecycleError::Overflow),
+            lifecycle.population(&timers, 1_000_000_000_000, Arithmetic::Checked)
+        );
+    }
+
+    #[test]
+    fn invalid_timer_check() {
+        assert_eq!(
+            Err(LifecycleError::Timer { timer: 9, max: 8 }),
+            Lifecycle::default().population(&[3, 9], 10, Arithmetic::Checked)
+        );
+        assert!(Lifecycle::default()
+            .series(&[9], 10, Arithmetic::Checked)
+            .is_err());
+
+        for first_delay in [0, 2] {
+            let lifecycle = Lifecycle {
+                spawn_period: 0,
+                first_delay,
+                max_spawns: None,
+            };
+
+            assert_eq!(
+                Err(LifecycleError::SpawnPeriod),
+                lifecycle.population(&[0], 10, Arithmetic::Checked)
+            );
+            assert_eq!(
+                Err(LifecycleError::SpawnPeriod),
+                lifecycle.series(&[0], 10, Arithmetic::Checked)
+            );
+        }
+    }
+
+    #[test]
+    fn no_spawns_check() {
+        let timers = parse(TEST_CASE.to_string());
+        let lifecycle = Lifecycle {
+            max_spawns: Some(0),
+            ..Lifecycle::default()
+        };
+
+        // every fish dies the day after its timer reaches zero
+        assert_eq!(
+            Ok(vec![5, 5, 4, 3, 1, 0, 0]),
+            lifecycle.series(&timers, 6, Arithmetic::Checked)
+        );
+        assert_eq!(
+            Ok(0),
+            lifecycle.population(&timers, 1000, Arithmetic::Checked)
+        );
+    }
+
+    #[test]
+    fn series_check() {
+        let series = Lifecycle::default()
+            .series(&parse(TEST_CASE.to_string()), 18, Arithmetic::Checked)
+            .unwrap();
+
+        assert_eq!(vec![5, 5, 6, 7, 9, 10], series[0..6]);
+        assert_eq!(26, series[18]);
+    }
+
+    #[test]
+    fn custom_lifecycle_check() {
+        let timers = parse(TEST_CASE.to_string());
+
+        for lifecycle in [
+            Lifecycle {
+                spawn_period: 5,
+                first_delay: 4,
+                max_spawns: None,
+            },
+            Lifecycle {
+                spawn_period: 7,
+                first_delay: 2,
+                max_spawns: Some(3),
+            },
+            Lifecycle {
+                spawn_period: 4,
+                first_delay: 1,
+                max_spawns: Some(1),
+            },
+            Lifecycle {
+                spawn_period: 6,
+                first_delay: 3,
+                max_spawns: Some(0),
+            },
+        ] {
+            for days in [0, 1, 9, 40] {
+                assert_eq!(
+                    Ok(simulate(&lifecycle, &timers, days)),
+                    lifecycle.population(&timers, days as u64, Arithmetic::Checked)
+                );
+            }
+        }
+    }
+
+    fn simulate(lifecycle: &Lifecycle, timers: &[usize], days: usize) -> u128 {
+        let mut fish: Vec<(usize, usize)> = timers.iter().map(|t| (*t, 0)).collect();
+
+        for _ in 0..days {
+            let mut next = Vec::new();
+
+            for (timer, spawns) in fish {
+                if timer > 0 {
+                    next.push((timer - 1, spawns));
+                    continue;
+                }
+
+                if lifecycle.max_spawns == Some(0) {
+                    continue;
+                }
+
+                next.push((lifecycle.spawn_period + lifecycle.first_delay - 1, 0));
+
+                if lifecycle.max_spawns.map(|m| spawns + 1 < m).unwrap_or(true) {
+                    next.push((lifecycle.spawn_period - 1, spawns + 1));
+                }
+            }
+
+            fish = next;
+        }
+
+        fish.len() as u128
+    }
 }
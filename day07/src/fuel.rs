@@ -0,0 +1,146 @@
+use std::{fmt, ops::RangeInclusive};
+
+// Costs have to be non-decreasing and convex in the distance, so that
+// the total fuel is convex in the alignment position. None when the
+// cost does not fit.
+pub trait FuelCost {
+    fn cost(&self, distance: u64) -> Option<u64>;
+}
+
+pub struct Linear;
+
+pub struct Triangular;
+
+pub struct Quadratic;
+
+pub struct Polynomial {
+    coefficients: Vec<u64>,
+}
+
+impl FuelCost for Linear {
+    fn cost(&self, distance: u64) -> Option<u64> {
+        Some(distance)
+    }
+}
+
+impl FuelCost for Triangular {
+    fn cost(&self, distance: u64) -> Option<u64> {
+        let cost = distance as u128 * (distance as u128 + 1) / 2;
+
+        u64::try_from(cost).ok()
+    }
+}
+
+impl FuelCost for Quadratic {
+    fn cost(&self, distance: u64) -> Option<u64> {
+        distance.checked_mul(distance)
+    }
+}
+
+impl Polynomial {
+    pub fn new(coefficients: Vec<u64>) -> Self {
+        Polynomial { coefficients }
+    }
+}
+
+impl FuelCost for Polynomial {
+    fn cost(&self, distance: u64) -> Option<u64> {
+        self.coefficients
+            .iter()
+            .rev()
+            .try_fold(0u64, |acc, c| acc.checked_mul(distance)?.checked_add(*c))
+    }
+}
+
+pub fn get_fuel_cost(name: &str) -> Box<dyn FuelCost> {
+    match name {
+        "linear" => Box::new(Linear),
+        "triangular" => Box::new(Triangular),
+        "quadratic" => Box::new(Quadratic),
+        x => Box::new(Polynomial::new(
+            x.split(',').map(|c| c.parse::<u64>().expect(x)).collect(),
+        )),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crab {
+    pub position: i64,
+    pub weight: u64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Alignment {
+    pub positions: RangeInclusive<i64>,
+    pub fuel: u64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AlignError {
+    NoCrabs,
+    Overflow,
+}
+
+impl fmt::Display for AlignError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AlignError::NoCrabs => write!(f, "No crabs to align"),
+            AlignError::Overflow => write!(f, "Fuel total overflows"),
+        }
+    }
+}
+
+// Total fuel is convex, so its minimum is where the difference to the next
+// position stops being negative, and all the optimal positions form a range
+// ending where the difference becomes positive.
+pub fn align(crabs: &[Crab], fuel_cost: &dyn FuelCost) -> Result<Alignment, AlignError> {
+    let min = crabs
+        .iter()
+        .map(|c| c.position)
+        .min()
+        .ok_or(AlignError::NoCrabs)?;
+    let max = crabs.iter().map(|c| c.position).max().unwrap_or(min);
+
+    // being convex, no total between the outermost crabs is above both of
+    // theirs, so once those fit every other one does too
+    get_total_fuel(crabs, fuel_cost, min)
+        .and(get_total_fuel(crabs, fuel_cost, max))
+        .ok_or(AlignError::Overflow)?;
+
+    let total = |t: i64| get_total_fuel(crabs, fuel_cost, t).unwrap() as i128;
+    let slope = |t: i64| total(t + 1) - total(t);
+
+    let first = find_first(min, max, |t| slope(t) >= 0);
+    let last = find_first(first, max, |t| slope(t) > 0);
+
+    Ok(Alignment {
+        positions: first..=last,
+        fuel: total(first) as u64,
+    })
+}
+
+pub fn get_total_fuel(crabs: &[Crab], fuel_cost: &dyn FuelCost, target: i64) -> Option<u64> {
+    crabs.iter().try_fold(0u64, |acc, c| {
+        let cost = fuel_cost.cost(target.abs_diff(c.position))?;
+
+        acc.checked_add(c.weight.checked_mul(cost)?)
+    })
+}
+
+// first position in the range satisfying a monotonic predicate,
+// the end of the range is returned when none does
+fn find_first(start: i64, end: i64, predicate: impl Fn(i64) -> bool) -> i64 {
+    let (mut lo, mut hi) = (start, end);
+
+    while lo < hi {
+        let mid = (lo as i128 + hi as i128).div_euclid(2) as i64;
+
+        if predicate(mid) {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+
+    lo
+}
@@ -1,8 +1,22 @@
-use std::fs;
+mod fuel;
+
+use std::{env, fs};
+
+use fuel::{align, get_fuel_cost, Crab, FuelCost, Linear, Triangular};
 
 fn main() {
     let input = fs::read_to_string("./_data/input.txt").expect("oh noes");
 
+    if let Some(name) = env::args().nth(1) {
+        match align(&parse(input.clone()), get_fuel_cost(&name).as_ref()) {
+            Ok(alignment) => println!(
+                "Custom result is: {} at {:?}",
+                alignment.fuel, alignment.positions
+            ),
+            Err(e) => println!("Custom alignment failed: {}", e),
+        }
+    }
+
     let output = process_data(input.clone());
     let adv_output = process_data_adv(input);
 
@@ -11,57 +25,44 @@ fn main() {
 }
 
 fn process_data(input: String) -> String {
-    let positions = parse(input);
-
-    let target = *positions.get(positions.len() / 2).unwrap();
-
-    positions
-        .iter()
-        .map(|p| (target - p).abs())
-        .sum::<i32>()
-        .to_string()
+    get_min_fuel(input, &Linear)
 }
 
 fn process_data_adv(input: String) -> String {
-    let positions = parse(input);
-
-    let average = positions.iter().sum::<i32>() as f64 / positions.len() as f64;
-    let rough_target = average.round() as i32;
-
-    [
-        get_true_sum_for_target(&positions, rough_target - 1),
-        get_true_sum_for_target(&positions, rough_target),
-        get_true_sum_for_target(&positions, rough_target + 1),
-    ]
-    .iter()
-    .min()
-    .unwrap()
-    .to_string()
+    get_min_fuel(input, &Triangular)
 }
 
-fn parse(input: String) -> Vec<i32> {
-    let mut positions: Vec<i32> = input
-        .trim()
-        .split(',')
-        .map(|s| s.parse::<i32>().unwrap())
-        .collect();
-
-    positions.sort_unstable();
-
-    positions
+fn get_min_fuel(input: String, fuel_cost: &dyn FuelCost) -> String {
+    align(&parse(input), fuel_cost)
+        .unwrap_or_else(|e| panic!("{}", e))
+        .fuel
+        .to_string()
 }
 
-fn get_true_sum_for_target(positions: &[i32], target: i32) -> i32 {
-    positions
-        .iter()
-        .map(|p| (target - p).abs())
-        .map(|d| (0..=d).sum::<i32>())
-        .sum()
+// crabs are given by position, optionally weighted as position:weight
+fn parse(input: String) -> Vec<Crab> {
+    input
+        .trim()
+        .split(',')
+        .map(|s| match s.split_once(':') {
+            Some((position, weight)) => Crab {
+                position: position.parse::<i64>().unwrap(),
+                weight: weight.parse::<u64>().unwrap(),
+            },
+            None => Crab {
+                position: s.parse::<i64>().unwrap(),
+                weight: 1,
+            },
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::*;
+    use crate::{
+        fuel::{get_total_fuel, AlignError, Alignment, Polynomial, Quadratic},
+        *,
+    };
 
     const TEST_CASE: &str = "16,1,2,0,4,2,7,1,2,14
     ";
@@ -75,4 +76,114 @@ mod tests {
     fn adv_check() {
         assert_eq!("168", process_data_adv(TEST_CASE.to_string()));
     }
+
+    #[test]
+    fn alignment_check() {
+        let crabs = parse(TEST_CASE.to_string());
+
+        assert_eq!(
+            Ok(Alignment {
+                positions: 2..=2,
+                fuel: 37
+            }),
+            align(&crabs, &Linear)
+        );
+        assert_eq!(
+            Ok(Alignment {
+                positions: 5..=5,
+                fuel: 168
+            }),
+            align(&crabs, &Triangular)
+        );
+        assert_eq!(Err(AlignError::NoCrabs), align(&[], &Linear));
+    }
+
+    #[test]
+    fn plateau_check() {
+        let crabs = parse("1,3,8,10".to_string());
+
+        assert_eq!(
+            Ok(Alignment {
+                positions: 3..=8,
+                fuel: 14
+            }),
+            align(&crabs, &Linear)
+        );
+    }
+
+    #[test]
+    fn custom_cost_check() {
+        let crabs = parse(TEST_CASE.to_string());
+
+        for fuel_cost in [
+            get_fuel_cost("quadratic"),
+            get_fuel_cost("3,0,1"),
+            get_fuel_cost("0,1,0,2"),
+        ] {
+            let brute_force = (0..=16)
+                .filter_map(|t| get_total_fuel(&crabs, fuel_cost.as_ref(), t))
+                .min()
+                .unwrap();
+
+            assert_eq!(brute_force, align(&crabs, fuel_cost.as_ref()).unwrap().fuel);
+        }
+
+        assert_eq!(
+            get_total_fuel(&crabs, &Triangular, 5).map(|f| f * 2),
+            get_total_fuel(&crabs, &Polynomial::new(vec![0, 1, 1]), 5)
+        );
+    }
+
+    #[test]
+    fn weighted_check() {
+        let crabs = vec![
+            Crab {
+                position: 0,
+                weight: 1,
+            },
+            Crab {
+                position: 10,
+                weight: 3,
+            },
+        ];
+
+        assert_eq!(crabs, parse("0,10:3".to_string()));
+
+        assert_eq!(
+            Ok(Alignment {
+                positions: 10..=10,
+                fuel: 10
+            }),
+            align(&crabs, &Linear)
+        );
+        assert_eq!(
+            Ok(Alignment {
+                positions: 7..=8,
+                fuel: 76
+            }),
+            align(&crabs, &Quadratic)
+        );
+    }
+
+    #[test]
+    fn overflow_check() {
+        let heavy = parse(format!("0:{},2", u64::MAX));
+
+        assert_eq!(Err(AlignError::Overflow), align(&heavy, &Linear));
+        assert_eq!(None, get_total_fuel(&heavy, &Linear, 1));
+        assert_eq!(
+            Err(AlignError::Overflow),
+            align(&parse("0,5000000000".to_string()), &Quadratic)
+        );
+        assert_eq!(None, Triangular.cost(u64::MAX));
+
+        let far_apart = parse("-5000000000000000000,5000000000000000000".to_string());
+        let alignment = align(&far_apart, &Linear).unwrap();
+
+        assert_eq!(10_000_000_000_000_000_000, alignment.fuel);
+        assert_eq!(
+            -5_000_000_000_000_000_000..=5_000_000_000_000_000_000,
+            alignment.positions
+        );
+    }
 }
@@ -0,0 +1,75 @@
+use std::collections::BTreeSet;
+
+const DIGITS: &str =
+    "0=abcefg 1=cf 2=acdeg 3=acdfg 4=bcdf 5=abdfg 6=abdefg 7=acf 8=abcdefg 9=abcdfg";
+
+const HEX_LETTERS: &str = "A=abcdef B=bdefg C=abeg D=cdefg E=abdeg F=abde";
+
+// a-f are the outer segments clockwise from the top, g and h the middle bar
+// halves, i, j, k the upper diagonal, vertical and diagonal, l, m, n the lower ones
+const ALPHANUMERICS: &str = "0=abcdefkl 1=bck 2=abdegh 3=abcdh 4=bcfgh 5=adfgn 6=acdefgh \
+    7=abc 8=abcdefgh 9=abcdfgh A=abcefgh B=abcdhjm C=adef D=abcdjm E=adefg F=aefg G=acdefh \
+    H=bcefgh I=adjm J=bcde K=efgkn L=def M=bcefik N=bcefin O=abcdef P=abefgh Q=abcdefn \
+    R=abefghn S=acdfgh T=ajm U=bcdef V=efkl W=bcefln X=ikln Y=ikm Z=adkl";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alphabet {
+    symbols: Vec<(char, BTreeSet<char>)>,
+}
+
+impl Alphabet {
+    pub fn seven_segment() -> Self {
+        Self::parse(DIGITS)
+    }
+
+    pub fn hexadecimal() -> Self {
+        Self::parse(&format!("{} {}", DIGITS, HEX_LETTERS))
+    }
+
+    pub fn fourteen_segment() -> Self {
+        Self::parse(ALPHANUMERICS)
+    }
+
+    // symbols are given as space separated "symbol=segments" entries
+    pub fn parse(spec: &str) -> Self {
+        let symbols = spec
+            .split_whitespace()
+            .map(|entry| {
+                let (symbol, segments) = entry.split_once('=').expect(entry);
+
+                (
+                    symbol.chars().next().expect(entry),
+                    segments.chars().collect(),
+                )
+            })
+            .collect();
+
+        Alphabet { symbols }
+    }
+
+    pub fn patterns(&self) -> Vec<BTreeSet<char>> {
+        self.symbols.iter().map(|(_, s)| s.clone()).collect()
+    }
+
+    pub fn symbols(&self) -> Vec<char> {
+        self.symbols.iter().map(|(symbol, _)| *symbol).collect()
+    }
+
+    pub fn segments(&self) -> BTreeSet<char> {
+        self.symbols.iter().flat_map(|(_, s)| s).copied().collect()
+    }
+
+    pub fn get(&self, symbol: char) -> Option<&BTreeSet<char>> {
+        self.symbols
+            .iter()
+            .find(|(s, _)| *s == symbol)
+            .map(|(_, segments)| segments)
+    }
+
+    pub fn lookup(&self, segments: &BTreeSet<char>) -> Option<char> {
+        self.symbols
+            .iter()
+            .find(|(_, s)| s == segments)
+            .map(|(symbol, _)| *symbol)
+    }
+}
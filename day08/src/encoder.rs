@@ -0,0 +1,96 @@
+use std::collections::BTreeSet;
+
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::{
+    alphabet::Alphabet,
+    wiring::{Wiring, WiringError},
+};
+
+pub fn encode_line(
+    alphabet: &Alphabet,
+    wiring: &Wiring,
+    text: &str,
+) -> Result<String, WiringError> {
+    let patterns = alphabet
+        .symbols()
+        .into_iter()
+        .map(|s| encode_symbol(alphabet, wiring, s))
+        .collect::<Result<Vec<String>, WiringError>>()?;
+
+    let outputs = text
+        .chars()
+        .map(|s| encode_symbol(alphabet, wiring, s))
+        .collect::<Result<Vec<String>, WiringError>>()?;
+
+    Ok(format!("{} | {}", patterns.join(" "), outputs.join(" ")))
+}
+
+pub fn get_random_wiring(alphabet: &Alphabet, rng: &mut StdRng) -> Wiring {
+    let segments: Vec<char> = alphabet.segments().into_iter().collect();
+    let mut wires = segments.clone();
+    wires.shuffle(rng);
+
+    Wiring::new(wires.into_iter().zip(segments).collect())
+}
+
+// returns the notes together with the texts hidden in them
+pub fn generate_notes(
+    alphabet: &Alphabet,
+    lines: usize,
+    length: usize,
+    seed: u64,
+) -> (String, Vec<String>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let symbols = alphabet.symbols();
+
+    let mut notes = Vec::with_capacity(lines);
+    let mut texts = Vec::with_capacity(lines);
+
+    for _ in 0..lines {
+        let wiring = get_random_wiring(alphabet, &mut rng);
+        let text: String = (0..length)
+            .map(|_| *symbols.choose(&mut rng).unwrap())
+            .collect();
+
+        let line = encode_line(alphabet, &wiring, &text).unwrap();
+
+        notes.push(shuffle_line(&line, &mut rng));
+        texts.push(text);
+    }
+
+    (notes.join("\n"), texts)
+}
+
+// the order of patterns and of wires within them carries no information
+fn shuffle_line(line: &str, rng: &mut StdRng) -> String {
+    let (patterns, outputs) = line.split_once(" | ").unwrap();
+
+    let mut patterns: Vec<&str> = patterns.split(' ').collect();
+    patterns.shuffle(rng);
+
+    let mut shuffle_wires = |pattern: &str| {
+        let mut wires: Vec<char> = pattern.chars().collect();
+        wires.shuffle(rng);
+        wires.into_iter().collect::<String>()
+    };
+
+    let patterns: Vec<String> = patterns.into_iter().map(&mut shuffle_wires).collect();
+    let outputs: Vec<String> = outputs.split(' ').map(&mut shuffle_wires).collect();
+
+    format!("{} | {}", patterns.join(" "), outputs.join(" "))
+}
+
+fn encode_symbol(
+    alphabet: &Alphabet,
+    wiring: &Wiring,
+    symbol: char,
+) -> Result<String, WiringError> {
+    let segments = alphabet
+        .get(symbol)
+        .ok_or(WiringError::UnknownSymbol(symbol))?;
+
+    let wires: BTreeSet<char> = wiring.encode(segments)?.wires;
+
+    Ok(wires.into_iter().collect())
+}
@@ -1,8 +1,42 @@
-use std::{collections::BTreeSet, fs};
+mod alphabet;
+mod encoder;
+mod wiring;
+
+use std::{collections::BTreeSet, env, fs};
+
+use alphabet::Alphabet;
+use encoder::generate_notes;
+use wiring::{Wiring, WiringError};
 
 fn main() {
     let input = fs::read_to_string("./_data/input.txt").expect("oh noes");
 
+    let args: Vec<String> = env::args().collect();
+
+    match args.get(1).map(|a| &a[..]) {
+        Some("generate") => {
+            let lines = args[2].parse::<usize>().unwrap();
+            let seed = args[3].parse::<u64>().unwrap();
+            let (notes, _) = generate_notes(&Alphabet::seven_segment(), lines, 4, seed);
+
+            fs::write(&args[4], notes + "\n").expect("oh noes");
+        }
+        Some(name) => {
+            let alphabet = get_alphabet(name);
+
+            for line in input.trim().lines() {
+                let decoded = parse_line(line)
+                    .and_then(|(inputs, outputs)| decode_line(&alphabet, inputs, outputs));
+
+                match decoded {
+                    Ok(text) => println!("{}", text),
+                    Err(e) => println!("{}", e),
+                }
+            }
+        }
+        None => {}
+    }
+
     let output = process_data(input.clone());
     let adv_output = process_data_adv(input);
 
@@ -10,6 +44,14 @@ fn main() {
     println!("Adv result is: {}", adv_output);
 }
 
+fn get_alphabet(name: &str) -> Alphabet {
+    match name {
+        "hex" => Alphabet::hexadecimal(),
+        "14" => Alphabet::fourteen_segment(),
+        path => Alphabet::parse(&fs::read_to_string(path).expect("oh noes")),
+    }
+}
+
 fn process_data(input: String) -> String {
     input
         .trim()
@@ -34,86 +76,64 @@ fn process_data_adv(input: String) -> String {
     input
         .trim()
         .lines()
-        .map(parse_line)
-        .map(|(i, t)| solve_line(i, t))
-        .sum::<u32>()
-        .to_string()
+        .map(|l| parse_line(l).and_then(|(i, t)| solve_line(i, t)))
+        .sum::<Result<u32, WiringError>>()
+        .map(|sum| sum.to_string())
+        .unwrap_or_else(|e| e.to_string())
 }
 
-fn parse_line(line: &str) -> (Vec<Segment>, Vec<Segment>) {
-    let segments: Vec<Segment> = line
-        .trim()
-        .split('|')
-        .flat_map(|s| s.trim().split(' '))
-        .map(|s| {
-            let mut wires = BTreeSet::new();
-            for c in s.chars() {
-                wires.insert(c);
-            }
+fn parse_line(line: &str) -> Result<(Vec<Segment>, Vec<Segment>), WiringError> {
+    let segments = |part: &str| {
+        part.split(' ')
+            .filter(|s| !s.is_empty())
+            .map(|s| Segment {
+                wires: s.chars().collect(),
+            })
+            .collect::<Vec<Segment>>()
+    };
 
-            Segment {
-                size: s.len(),
-                wires,
-            }
-        })
-        .collect();
+    match line.trim().split_once('|') {
+        Some((inputs, outputs)) if !outputs.contains('|') => {
+            Ok((segments(inputs), segments(outputs)))
+        }
+        _ => Err(WiringError::MalformedNote(line.trim().to_string())),
+    }
+}
+
+fn solve_line(inputs: Vec<Segment>, targets: Vec<Segment>) -> Result<u32, WiringError> {
+    let text = decode_line(&Alphabet::seven_segment(), inputs, targets)?;
 
-    let inputs = segments.get(0..10).unwrap();
-    let outputs = segments.get(10..).unwrap();
+    // notes without outputs read as 0
+    text.chars().try_fold(0u32, |acc, c| {
+        let digit = c.to_digit(10).ok_or(WiringError::UnknownSymbol(c))?;
 
-    (inputs.to_vec(), outputs.to_vec())
+        acc.checked_mul(10)
+            .and_then(|acc| acc.checked_add(digit))
+            .ok_or_else(|| WiringError::Overflow(text.clone()))
+    })
 }
 
-fn solve_line(inputs: Vec<Segment>, targets: Vec<Segment>) -> u32 {
-    let &one = inputs
-        .iter()
-        .filter(|&s| s.size == 2)
-        .collect::<Vec<&Segment>>()
-        .get(0)
-        .unwrap();
+fn decode_line(
+    alphabet: &Alphabet,
+    inputs: Vec<Segment>,
+    targets: Vec<Segment>,
+) -> Result<String, WiringError> {
+    let wiring = Wiring::solve(alphabet, &inputs)?;
 
-    let &four = inputs
+    targets
         .iter()
-        .filter(|&s| s.size == 4)
-        .collect::<Vec<&Segment>>()
-        .get(0)
-        .unwrap();
-
-    let mut number = 0u32;
-
-    for (index, target) in targets.iter().rev().enumerate() {
-        let sans_one = target.wires.difference(&one.wires).count();
-        let sans_four = target.wires.difference(&four.wires).count();
-
-        let num = match (target.size, sans_one, sans_four) {
-            (2, _, _) => 1,
-            (3, _, _) => 7,
-            (4, _, _) => 4,
-            (7, _, _) => 8,
-            (5, 4, 3) => 2,
-            (5, 3, 2) => 3,
-            (5, 4, 2) => 5,
-            (6, 4, 3) => 0,
-            (6, 5, 3) => 6,
-            (6, 4, 2) => 9,
-            (x, y, z) => panic!("{}, {}, {}", x, y, z),
-        };
-
-        number += num * 10u32.pow(index.try_into().unwrap());
-    }
-
-    number
+        .map(|target| wiring.decode(alphabet, target))
+        .collect()
 }
 
 #[derive(Debug, Clone)]
 struct Segment {
-    size: usize,
     wires: BTreeSet<char>,
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::*;
+    use crate::{encoder::encode_line, *};
 
     const TEST_CASE: &str =
         "be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe
@@ -133,9 +153,180 @@ mod tests {
         let line =
             "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf";
 
-        let (inputs, outputs) = parse_line(line);
+        let (inputs, outputs) = parse_line(line).unwrap();
+
+        assert_eq!(Ok(5353), solve_line(inputs, outputs));
+    }
+
+    #[test]
+    fn wiring_check() {
+        let line =
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf";
+
+        let (inputs, _) = parse_line(line).unwrap();
+
+        assert_eq!(
+            "a->c b->f c->g d->a e->b f->d g->e",
+            Wiring::solve(&Alphabet::seven_segment(), &inputs)
+                .unwrap()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn invalid_line_check() {
+        let duplicate =
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb cagedb | cdfeb fcadb cdfeb cdbaf";
+        let contradictory =
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb abc | cdfeb fcadb cdfeb cdbaf";
+        let unknown =
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdb";
+
+        let (inputs, outputs) = parse_line(duplicate).unwrap();
+        assert_eq!(
+            Err(WiringError::PatternCount(10, 9)),
+            solve_line(inputs, outputs)
+        );
+
+        let (inputs, outputs) = parse_line(contradictory).unwrap();
+        assert_eq!(Err(WiringError::Contradictory), solve_line(inputs, outputs));
+
+        let (inputs, outputs) = parse_line(unknown).unwrap();
+        assert_eq!(
+            Err(WiringError::UnknownPattern("bcd".to_string())),
+            solve_line(inputs, outputs)
+        );
 
-        assert_eq!(5353, solve_line(inputs, outputs));
+        for malformed in ["acedgfb cdfbe", "ab | cd | ef"] {
+            assert!(matches!(
+                parse_line(malformed),
+                Err(WiringError::MalformedNote(_))
+            ));
+        }
+        assert_eq!(
+            "Note \"ab cd\" is not patterns | outputs",
+            process_data_adv("ab cd".to_string())
+        );
+    }
+
+    #[test]
+    fn output_value_check() {
+        let patterns = "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab";
+
+        let (inputs, outputs) = parse_line(&format!("{} |", patterns)).unwrap();
+        assert_eq!(Ok(0), solve_line(inputs, outputs));
+
+        let (inputs, outputs) =
+            parse_line(&format!("{} | {}", patterns, "cdfeb ".repeat(10))).unwrap();
+        assert_eq!(
+            Err(WiringError::Overflow("5555555555".to_string())),
+            solve_line(inputs, outputs)
+        );
+    }
+
+    #[test]
+    fn hexadecimal_check() {
+        // wiring a->c b->f c->g d->a e->b f->d g->e
+        let line = "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab \
+            deafgb efgbc degc afgbc defgc defg | ab dab deafgb efgbc";
+
+        let (inputs, outputs) = parse_line(line).unwrap();
+
+        assert_eq!(
+            Ok("17AB".to_string()),
+            decode_line(&Alphabet::hexadecimal(), inputs, outputs)
+        );
+    }
+
+    #[test]
+    fn fourteen_segment_check() {
+        let alphabet = Alphabet::fourteen_segment();
+        let scramble = |segments: &BTreeSet<char>| Segment {
+            wires: segments
+                .iter()
+                .map(|s| {
+                    "nkcmjgbledhaif"
+                        .chars()
+                        .nth(*s as usize - 'a' as usize)
+                        .unwrap()
+                })
+                .collect(),
+        };
+
+        let inputs: Vec<Segment> = alphabet.patterns().iter().map(scramble).collect();
+        let outputs: Vec<Segment> = "SEGMENT7"
+            .chars()
+            .map(|c| {
+                let index = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ".find(c).unwrap();
+                inputs[index].clone()
+            })
+            .collect();
+
+        assert_eq!(
+            Ok("SEGMENT7".to_string()),
+            decode_line(&alphabet, inputs, outputs)
+        );
+    }
+
+    #[test]
+    fn ambiguous_alphabet_check() {
+        let alphabet = Alphabet::parse("1=ab 2=bc 3=ac");
+        let (inputs, outputs) = parse_line("xy yz xz | xy").unwrap();
+
+        assert!(matches!(
+            decode_line(&alphabet, inputs, outputs),
+            Err(WiringError::Ambiguous(_, _))
+        ));
+    }
+
+    #[test]
+    fn encode_line_check() {
+        let wiring = Wiring::new("abcdefg".chars().zip("cfgabde".chars()).collect());
+
+        let line = encode_line(&Alphabet::seven_segment(), &wiring, "5353").unwrap();
+        let (_, outputs) = line.split_once(" | ").unwrap();
+
+        assert_eq!("bcdef abcdf bcdef abcdf", outputs);
+        assert_eq!(
+            Err(WiringError::UnknownSymbol('A')),
+            encode_line(&Alphabet::seven_segment(), &wiring, "5A")
+        );
+    }
+
+    #[test]
+    fn round_trip_check() {
+        let (notes, texts) = generate_notes(&Alphabet::seven_segment(), 50, 4, 8);
+
+        for (line, text) in notes.lines().zip(texts.iter()) {
+            let (inputs, outputs) = parse_line(line).unwrap();
+
+            assert_eq!(
+                Ok(text.parse::<u32>().unwrap()),
+                solve_line(inputs, outputs)
+            );
+        }
+
+        assert_eq!(
+            notes,
+            generate_notes(&Alphabet::seven_segment(), 50, 4, 8).0
+        );
+        assert_ne!(
+            notes,
+            generate_notes(&Alphabet::seven_segment(), 50, 4, 9).0
+        );
+    }
+
+    #[test]
+    fn alphabet_round_trip_check() {
+        for alphabet in [Alphabet::hexadecimal(), Alphabet::fourteen_segment()] {
+            let (notes, texts) = generate_notes(&alphabet, 10, 6, 8);
+
+            for (line, text) in notes.lines().zip(texts) {
+                let (inputs, outputs) = parse_line(line).unwrap();
+
+                assert_eq!(Ok(text), decode_line(&alphabet, inputs, outputs));
+            }
+        }
     }
 
     #[test]
@@ -0,0 +1,208 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use crate::{alphabet::Alphabet, Segment};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WiringError {
+    PatternCount(usize, usize),
+    Contradictory,
+    Ambiguous(Wiring, Wiring),
+    UnknownPattern(String),
+    UnknownSymbol(char),
+    MalformedNote(String),
+    Overflow(String),
+}
+
+impl fmt::Display for WiringError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WiringError::PatternCount(expected, count) => {
+                write!(f, "Expected {} unique patterns, got {}", expected, count)
+            }
+            WiringError::Contradictory => write!(f, "No wiring matches the patterns"),
+            WiringError::Ambiguous(a, b) => {
+                write!(f, "Patterns match multiple wirings: {} and {}", a, b)
+            }
+            WiringError::UnknownPattern(p) => write!(f, "Pattern {} is not a known symbol", p),
+            WiringError::UnknownSymbol(s) => write!(f, "Symbol {} is not in the alphabet", s),
+            WiringError::MalformedNote(line) => {
+                write!(f, "Note {:?} is not patterns | outputs", line)
+            }
+            WiringError::Overflow(text) => write!(f, "Output {} does not fit in 32 bits", text),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wiring {
+    mapping: BTreeMap<char, char>,
+}
+
+impl fmt::Display for Wiring {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pairs: Vec<String> = self
+            .mapping
+            .iter()
+            .map(|(wire, segment)| format!("{}->{}", wire, segment))
+            .collect();
+
+        write!(f, "{}", pairs.join(" "))
+    }
+}
+
+impl Wiring {
+    pub fn new(mapping: BTreeMap<char, char>) -> Self {
+        Wiring { mapping }
+    }
+
+    pub fn solve(alphabet: &Alphabet, patterns: &[Segment]) -> Result<Self, WiringError> {
+        let symbols = alphabet.patterns();
+        let unique: BTreeSet<&BTreeSet<char>> = patterns.iter().map(|p| &p.wires).collect();
+
+        if unique.len() != symbols.len() {
+            return Err(WiringError::PatternCount(symbols.len(), unique.len()));
+        }
+
+        let patterns: Vec<BTreeSet<char>> = unique.into_iter().cloned().collect();
+        let wires: BTreeSet<char> = patterns.iter().flatten().copied().collect();
+        let segments = alphabet.segments();
+
+        if wires.len() != segments.len() {
+            return Err(WiringError::Contradictory);
+        }
+
+        // a wire can only drive a segment which is lit in the same number
+        // of patterns of each size
+        let candidates: Vec<(char, Vec<char>)> = wires
+            .iter()
+            .map(|w| {
+                let signature = get_signature(&patterns, *w);
+
+                (
+                    *w,
+                    segments
+                        .iter()
+                        .filter(|s| get_signature(&symbols, **s) == signature)
+                        .copied()
+                        .collect(),
+                )
+            })
+            .collect();
+
+        let mut solutions = Vec::new();
+        let mut search = Search {
+            patterns: &patterns,
+            symbols: &symbols,
+            candidates: &candidates,
+            mapping: BTreeMap::new(),
+        };
+
+        search.run(0, &mut solutions);
+
+        match solutions.len() {
+            0 => Err(WiringError::Contradictory),
+            1 => Ok(solutions.remove(0)),
+            _ => Err(WiringError::Ambiguous(
+                solutions.remove(0),
+                solutions.remove(0),
+            )),
+        }
+    }
+
+    pub fn encode(&self, segments: &BTreeSet<char>) -> Result<Segment, WiringError> {
+        let wires = segments
+            .iter()
+            .map(|s| {
+                self.mapping
+                    .iter()
+                    .find(|(_, segment)| *segment == s)
+                    .map(|(wire, _)| *wire)
+            })
+            .collect::<Option<BTreeSet<char>>>()
+            .ok_or_else(|| WiringError::UnknownPattern(get_name(segments)))?;
+
+        Ok(Segment { wires })
+    }
+
+    pub fn decode(&self, alphabet: &Alphabet, pattern: &Segment) -> Result<char, WiringError> {
+        let segments: BTreeSet<char> = pattern
+            .wires
+            .iter()
+            .map(|w| self.mapping.get(w).copied())
+            .collect::<Option<BTreeSet<char>>>()
+            .ok_or_else(|| WiringError::UnknownPattern(get_name(&pattern.wires)))?;
+
+        alphabet
+            .lookup(&segments)
+            .ok_or_else(|| WiringError::UnknownPattern(get_name(&pattern.wires)))
+    }
+}
+
+struct Search<'a> {
+    patterns: &'a [BTreeSet<char>],
+    symbols: &'a [BTreeSet<char>],
+    candidates: &'a [(char, Vec<char>)],
+    mapping: BTreeMap<char, char>,
+}
+
+impl<'a> Search<'a> {
+    // two solutions are enough to tell that the wiring is ambiguous
+    fn run(&mut self, index: usize, solutions: &mut Vec<Wiring>) {
+        if solutions.len() > 1 || !self.is_consistent() {
+            return;
+        }
+
+        if index == self.candidates.len() {
+            solutions.push(Wiring {
+                mapping: self.mapping.clone(),
+            });
+            return;
+        }
+
+        let (wire, segments) = &self.candidates[index];
+
+        for segment in segments {
+            if self.mapping.values().any(|s| s == segment) {
+                continue;
+            }
+
+            self.mapping.insert(*wire, *segment);
+            self.run(index + 1, solutions);
+            self.mapping.remove(wire);
+        }
+    }
+
+    // every pattern has to still fit into some symbol of the same size
+    fn is_consistent(&self) -> bool {
+        self.patterns.iter().all(|p| {
+            let mapped: BTreeSet<char> = p
+                .iter()
+                .filter_map(|w| self.mapping.get(w))
+                .copied()
+                .collect();
+
+            self.symbols
+                .iter()
+                .any(|s| s.len() == p.len() && mapped.is_subset(s))
+        })
+    }
+}
+
+fn get_signature(patterns: &[BTreeSet<char>], c: char) -> Vec<usize> {
+    let mut sizes: Vec<usize> = patterns
+        .iter()
+        .filter(|p| p.contains(&c))
+        .map(|p| p.len())
+        .collect();
+
+    sizes.sort_unstable();
+
+    sizes
+}
+
+fn get_name(wires: &BTreeSet<char>) -> String {
+    wires.iter().collect()
+}
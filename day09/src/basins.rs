@@ -0,0 +1,187 @@
+use std::collections::BTreeSet;
+
+use crate::{get_basin_cells, get_low_points, get_neighbors, HeightMap};
+
+const LABELS: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Basin {
+    pub low_point: usize,
+    pub cells: Vec<usize>,
+    // cells bordering a wall or another basin, the map edge does not count
+    pub boundary: Vec<usize>,
+}
+
+pub struct Segmentation {
+    width: usize,
+    wall: u32,
+    heights: Vec<u32>,
+    labels: Vec<Option<usize>>,
+    basins: Vec<Basin>,
+}
+
+impl Segmentation {
+    // cells reachable from more than one low point stay with the first basin
+    pub fn new(heightmap: &HeightMap) -> Self {
+        let mut labels = vec![None; heightmap.values.len()];
+        let mut basin_cells = Vec::new();
+
+        for (low_point, _) in get_low_points(heightmap) {
+            let label = basin_cells.len();
+            let cells: BTreeSet<usize> = get_basin_cells(low_point, heightmap)
+                .into_iter()
+                .filter(|c| labels[*c].is_none())
+                .collect();
+
+            for c in cells.iter() {
+                labels[*c] = Some(label);
+            }
+
+            basin_cells.push((low_point, cells));
+        }
+
+        let basins = basin_cells
+            .into_iter()
+            .enumerate()
+            .map(|(label, (low_point, cells))| {
+                let boundary = cells
+                    .iter()
+                    .filter(|c| {
+                        get_neighbors(**c, heightmap)
+                            .iter()
+                            .any(|(n, _)| labels[*n] != Some(label))
+                    })
+                    .copied()
+                    .collect();
+
+                Basin {
+                    low_point,
+                    cells: cells.into_iter().collect(),
+                    boundary,
+                }
+            })
+            .collect();
+
+        Segmentation {
+            width: heightmap.width,
+            wall: heightmap.wall,
+            heights: heightmap.values.clone(),
+            labels,
+            basins,
+        }
+    }
+
+    pub fn basins(&self) -> &[Basin] {
+        &self.basins
+    }
+
+    pub fn to_ascii(&self) -> String {
+        self.labels
+            .chunks(self.width)
+            .zip(self.heights.chunks(self.width))
+            .map(|(labels, heights)| {
+                labels
+                    .iter()
+                    .zip(heights)
+                    .map(|(label, height)| match label {
+                        Some(l) => LABELS.chars().nth(l % LABELS.len()).unwrap(),
+                        None if *height >= self.wall => '#',
+                        None => '.',
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut output = format!(
+            "P6\n{} {}\n255\n",
+            self.width,
+            self.labels.len() / self.width
+        )
+        .into_bytes();
+
+        for (label, height) in self.labels.iter().zip(self.heights.iter()) {
+            let color = match label {
+                Some(l) => get_basin_color(*l, *height),
+                None if *height >= self.wall => [0, 0, 0],
+                None => [255, 255, 255],
+            };
+
+            output.extend(color);
+        }
+
+        output
+    }
+
+    pub fn to_json(&self) -> String {
+        let labels = self
+            .labels
+            .chunks(self.width)
+            .map(|row| {
+                let row: Vec<String> = row
+                    .iter()
+                    .map(|l| {
+                        l.map(|l| l.to_string())
+                            .unwrap_or_else(|| "null".to_string())
+                    })
+                    .collect();
+
+                format!("[{}]", row.join(","))
+            })
+            .collect::<Vec<String>>();
+
+        let basins = self
+            .basins
+            .iter()
+            .enumerate()
+            .map(|(label, basin)| {
+                format!(
+                    "{{\"label\":{},\"size\":{},\"low_point\":{},\"cells\":{},\"boundary\":{}}}",
+                    label,
+                    basin.cells.len(),
+                    self.get_json_point(basin.low_point),
+                    self.get_json_points(&basin.cells),
+                    self.get_json_points(&basin.boundary)
+                )
+            })
+            .collect::<Vec<String>>();
+
+        format!(
+            "{{\"width\":{},\"height\":{},\"labels\":[{}],\"basins\":[{}]}}",
+            self.width,
+            self.labels.len() / self.width,
+            labels.join(","),
+            basins.join(",")
+        )
+    }
+
+    fn get_json_point(&self, position: usize) -> String {
+        format!("[{},{}]", position % self.width, position / self.width)
+    }
+
+    fn get_json_points(&self, positions: &[usize]) -> String {
+        let points: Vec<String> = positions.iter().map(|p| self.get_json_point(*p)).collect();
+
+        format!("[{}]", points.join(","))
+    }
+}
+
+// distinct hue per basin, darker at the bottom
+fn get_basin_color(label: usize, height: u32) -> [u8; 3] {
+    let hue = (label * 137) % 360;
+    let value = 255 - (8 - height.min(8)) as usize * 16;
+    let x = value * (60 - (hue % 120).abs_diff(60)) / 60;
+
+    let (r, g, b) = match hue / 60 {
+        0 => (value, x, 0),
+        1 => (x, value, 0),
+        2 => (0, value, x),
+        3 => (0, x, value),
+        4 => (x, 0, value),
+        _ => (value, 0, x),
+    };
+
+    [r as u8, g as u8, b as u8]
+}
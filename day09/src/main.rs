@@ -1,7 +1,26 @@
-use std::{collections::BTreeSet, fs};
+mod basins;
+
+use std::{collections::BTreeSet, env, fmt, fs};
+
+use basins::Segmentation;
 
 fn main() {
     let input = fs::read_to_string("./_data/input.txt").expect("oh noes");
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if !args.is_empty() {
+        match parse_heightmap(input.clone(), &get_options(&args)) {
+            Ok(heightmap) => {
+                println!("Custom result is: {}", get_risk_level(&heightmap));
+                println!("Custom adv result is: {}", get_basins_product(&heightmap));
+
+                if let Some(path) = args.iter().find(|a| !a.starts_with("--")) {
+                    export(&heightmap, path);
+                }
+            }
+            Err(e) => println!("{}", e),
+        }
+    }
 
     let output = process_data(input.clone());
     let adv_output = process_data_adv(input);
@@ -11,35 +30,65 @@ fn main() {
 }
 
 fn process_data(input: String) -> String {
-    let heightmap = get_heightmap(input);
-
-    get_low_points(&heightmap)
-        .iter()
-        .map(|(_, h)| h + 1)
-        .sum::<u32>()
-        .to_string()
+    get_risk_level(&get_heightmap(input)).to_string()
 }
 
 fn process_data_adv(input: String) -> String {
-    let heightmap = get_heightmap(input);
+    get_basins_product(&get_heightmap(input)).to_string()
+}
+
+// flags are --eight, --pad and --wall=N, anything else is an export path
+fn get_options(args: &[String]) -> Options {
+    let mut options = Options::default();
 
-    let mut basins: Vec<usize> = get_low_points(&heightmap)
+    for arg in args {
+        match arg.split_once('=') {
+            Some(("--wall", wall)) => options.wall = wall.parse::<u32>().unwrap(),
+            _ if arg == "--eight" => options.connectivity = Connectivity::Eight,
+            _ if arg == "--pad" => options.pad_ragged = true,
+            _ => {}
+        }
+    }
+
+    options
+}
+
+fn export(heightmap: &HeightMap, path: &str) {
+    let segmentation = Segmentation::new(heightmap);
+
+    let content = match path.rsplit('.').next() {
+        Some("json") => segmentation.to_json().into_bytes(),
+        Some("ppm") => segmentation.to_ppm(),
+        _ => segmentation.to_ascii().into_bytes(),
+    };
+
+    fs::write(path, content).expect("oh noes");
+}
+
+fn get_risk_level(heightmap: &HeightMap) -> u32 {
+    get_low_points(heightmap).iter().map(|(_, h)| h + 1).sum()
+}
+
+// maps with fewer than three basins multiply the ones they have,
+// a map without any has a product of zero
+fn get_basins_product(heightmap: &HeightMap) -> usize {
+    let mut basins: Vec<usize> = Segmentation::new(heightmap)
+        .basins()
         .iter()
-        .map(|(i, _)| get_basin_size(*i, &heightmap))
+        .map(|b| b.cells.len())
         .collect();
 
     basins.sort_unstable();
     basins.reverse();
 
-    basins
-        .get(0..3)
-        .unwrap()
-        .iter()
-        .product::<usize>()
-        .to_string()
+    if basins.is_empty() {
+        return 0;
+    }
+
+    basins.iter().take(3).product()
 }
 
-fn get_basin_size(position: usize, heightmap: &HeightMap) -> usize {
+fn get_basin_cells(position: usize, heightmap: &HeightMap) -> BTreeSet<usize> {
     let mut bas = BTreeSet::new();
     let mut to_do = Vec::<(usize, u32)>::new();
 
@@ -50,14 +99,14 @@ fn get_basin_size(position: usize, heightmap: &HeightMap) -> usize {
         let neigh = get_neighbors(pos, heightmap);
 
         for (i, v) in neigh.iter() {
-            if !bas.contains(i) && *v != 9 && *v > height {
+            if !bas.contains(i) && *v < heightmap.wall && *v > height {
                 bas.insert(*i);
                 to_do.push((*i, *v));
             }
         }
     }
 
-    bas.len()
+    bas
 }
 
 fn get_low_points(heightmap: &HeightMap) -> Vec<(usize, u32)> {
@@ -65,58 +114,168 @@ fn get_low_points(heightmap: &HeightMap) -> Vec<(usize, u32)> {
         .values
         .iter()
         .enumerate()
+        .filter(|(_, h)| **h < heightmap.wall)
         .filter(|(i, h)| get_neighbors(*i, heightmap).iter().all(|(_, v)| v > h))
         .map(|(i, &h)| (i, h))
         .collect()
 }
 
 fn get_heightmap(input: String) -> HeightMap {
-    let size = input.lines().next().unwrap().len();
-    let values: Vec<u32> = input
+    parse_heightmap(input, &Options::default()).unwrap()
+}
+
+fn parse_heightmap(input: String, options: &Options) -> Result<HeightMap, HeightMapError> {
+    let rows: Vec<Vec<u32>> = input
         .trim()
         .lines()
         .map(|l| l.trim())
-        .flat_map(|line| line.chars().map(|c| c.to_digit(10).unwrap()))
-        .collect();
+        .enumerate()
+        .map(|(row, line)| {
+            line.chars()
+                .enumerate()
+                .map(|(column, c)| {
+                    c.to_digit(10)
+                        .ok_or(HeightMapError::InvalidHeight { row, column, c })
+                })
+                .collect()
+        })
+        .collect::<Result<_, _>>()?;
 
-    HeightMap {
-        size,
-        total: values.len(),
-        values,
+    // the widest row sets the width, any shorter one is ragged
+    let width = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+
+    if width == 0 {
+        return Err(HeightMapError::Empty);
+    }
+
+    let mut values = Vec::with_capacity(width * rows.len());
+
+    for (row, mut heights) in rows.into_iter().enumerate() {
+        if heights.len() != width {
+            if !options.pad_ragged {
+                return Err(HeightMapError::RaggedRow {
+                    row,
+                    expected: width,
+                    found: heights.len(),
+                });
+            }
+
+            heights.resize(width, options.wall);
+        }
+
+        values.extend(heights);
     }
+
+    Ok(HeightMap {
+        width,
+        height: values.len() / width,
+        values,
+        connectivity: options.connectivity,
+        wall: options.wall,
+    })
 }
 
 fn get_neighbors(position: usize, heightmap: &HeightMap) -> Vec<(usize, u32)> {
-    let mut res = Vec::with_capacity(4);
-    let size = heightmap.size;
+    let offsets: &[(isize, isize)] = match heightmap.connectivity {
+        Connectivity::Four => &[(-1, 0), (1, 0), (0, -1), (0, 1)],
+        Connectivity::Eight => &[
+            (-1, 0),
+            (1, 0),
+            (0, -1),
+            (0, 1),
+            (-1, -1),
+            (1, -1),
+            (-1, 1),
+            (1, 1),
+        ],
+    };
 
-    if position % size != 0 {
-        let i = position - 1;
-        res.push((i, *heightmap.values.get(i).unwrap()));
-    }
+    let x = (position % heightmap.width) as isize;
+    let y = (position / heightmap.width) as isize;
 
-    if position % size != size - 1 {
-        let i = position + 1;
-        res.push((i, *heightmap.values.get(i).unwrap()));
-    }
+    offsets
+        .iter()
+        .map(|(dx, dy)| (x + dx, y + dy))
+        .filter(|(nx, ny)| {
+            *nx >= 0
+                && *ny >= 0
+                && (*nx as usize) < heightmap.width
+                && (*ny as usize) < heightmap.height
+        })
+        .map(|(nx, ny)| {
+            let i = ny as usize * heightmap.width + nx as usize;
+            (i, *heightmap.values.get(i).unwrap())
+        })
+        .collect()
+}
 
-    if position >= size {
-        let i = position - size;
-        res.push((i, *heightmap.values.get(i).unwrap()));
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Connectivity {
+    Four,
+    Eight,
+}
+
+struct Options {
+    connectivity: Connectivity,
+    wall: u32,
+    pad_ragged: bool,
+}
 
-    if position < (heightmap.total - size) {
-        let i = position + size;
-        res.push((i, *heightmap.values.get(i).unwrap()));
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            connectivity: Connectivity::Four,
+            wall: 9,
+            pad_ragged: false,
+        }
     }
+}
 
-    res
+#[derive(Debug, PartialEq, Eq)]
+enum HeightMapError {
+    Empty,
+    InvalidHeight {
+        row: usize,
+        column: usize,
+        c: char,
+    },
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for HeightMapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HeightMapError::Empty => write!(f, "Height map is empty"),
+            HeightMapError::InvalidHeight { row, column, c } => {
+                write!(
+                    f,
+                    "Invalid height '{}' at row {}, column {}",
+                    c, row, column
+                )
+            }
+            HeightMapError::RaggedRow {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Row {} has {} heights, expected {}",
+                row, found, expected
+            ),
+        }
+    }
 }
 
 struct HeightMap {
-    size: usize,
-    total: usize,
+    width: usize,
+    height: usize,
     values: Vec<u32>,
+    connectivity: Connectivity,
+    wall: u32,
 }
 
 #[cfg(test)]
@@ -139,4 +298,145 @@ mod tests {
     fn adv_check() {
         assert_eq!("1134", process_data_adv(TEST_CASE.to_string()));
     }
+
+    #[test]
+    fn rectangular_check() {
+        let heightmap = get_heightmap("  2199\n  3987\n  9856\n".to_string());
+
+        assert_eq!((4, 3), (heightmap.width, heightmap.height));
+        assert_eq!(vec![(1, 1), (10, 5)], get_low_points(&heightmap));
+        assert_eq!(vec![(0, 2), (2, 9), (5, 9)], get_neighbors(1, &heightmap));
+    }
+
+    #[test]
+    fn invalid_heightmap_check() {
+        let options = Options::default();
+
+        assert_eq!(
+            Err(HeightMapError::RaggedRow {
+                row: 1,
+                expected: 4,
+                found: 3
+            }),
+            parse_heightmap("2199\n398\n9856".to_string(), &options).map(|_| ())
+        );
+        assert_eq!(
+            Err(HeightMapError::InvalidHeight {
+                row: 2,
+                column: 1,
+                c: 'x'
+            }),
+            parse_heightmap("2199\n3987\n9x56".to_string(), &options).map(|_| ())
+        );
+        assert_eq!(
+            Err(HeightMapError::Empty),
+            parse_heightmap("\n".to_string(), &options).map(|_| ())
+        );
+
+        let padded = parse_heightmap(
+            "2199\n398\n9856".to_string(),
+            &Options {
+                pad_ragged: true,
+                ..Options::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(9, padded.values[7]);
+    }
+
+    #[test]
+    fn connectivity_check() {
+        let heightmap = parse_heightmap(
+            TEST_CASE.to_string(),
+            &Options {
+                connectivity: Connectivity::Eight,
+                ..Options::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(8, get_neighbors(11, &heightmap).len());
+        assert_eq!(3, get_neighbors(0, &heightmap).len());
+        assert_eq!(
+            vec![(1, 1), (9, 0), (22, 5), (46, 5)],
+            get_low_points(&heightmap)
+        );
+    }
+
+    #[test]
+    fn wall_check() {
+        let heightmap = parse_heightmap(
+            TEST_CASE.to_string(),
+            &Options {
+                wall: 7,
+                ..Options::default()
+            },
+        )
+        .unwrap();
+
+        let segmentation = Segmentation::new(&heightmap);
+
+        let expected = "aa###bbbbb
+a#####b#bb
+##cc#####b
+##c###d###
+#####ddd##";
+
+        assert_eq!(expected, segmentation.to_ascii());
+    }
+
+    #[test]
+    fn few_basins_check() {
+        assert_eq!(
+            3,
+            get_basins_product(&get_heightmap("123\n999".to_string()))
+        );
+        assert_eq!(
+            6,
+            get_basins_product(&get_heightmap("21912\n99929".to_string()))
+        );
+        assert_eq!(0, get_basins_product(&get_heightmap("999".to_string())));
+    }
+
+    #[test]
+    fn segmentation_check() {
+        let segmentation = Segmentation::new(&get_heightmap(TEST_CASE.to_string()));
+
+        let expected = "aa###bbbbb
+a#ccc#b#bb
+#ccccc#d#b
+ccccc#ddd#
+#c###ddddd";
+
+        assert_eq!(expected, segmentation.to_ascii());
+        assert_eq!(
+            vec![3, 9, 14, 9],
+            segmentation
+                .basins()
+                .iter()
+                .map(|b| b.cells.len())
+                .collect::<Vec<usize>>()
+        );
+
+        let first = &segmentation.basins()[0];
+        assert_eq!(1, first.low_point);
+        assert_eq!(vec![1, 10], first.boundary);
+    }
+
+    #[test]
+    fn export_check() {
+        let segmentation = Segmentation::new(&get_heightmap(TEST_CASE.to_string()));
+
+        let json = segmentation.to_json();
+        assert!(json.starts_with("{\"width\":10,\"height\":5,\"labels\":[[0,0,null,null,null,1,"));
+        assert!(json.contains(
+            "{\"label\":0,\"size\":3,\"low_point\":[1,0],\"cells\":[[0,0],[1,0],[0,1]],\"boundary\":[[1,0],[0,1]]}"
+        ));
+
+        let ppm = segmentation.to_ppm();
+        assert!(ppm.starts_with(b"P6\n10 5\n255\n"));
+        assert_eq!(12 + 150, ppm.len());
+        assert_eq!([0, 0, 0], ppm[12 + 6..12 + 9]);
+    }
 }
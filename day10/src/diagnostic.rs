@@ -0,0 +1,139 @@
+use std::fmt;
+
+use crate::grammar::{Grammar, Parse, Token};
+
+// lines and columns count from 1, columns in characters
+#[derive(Debug, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    Mismatched {
+        expected: String,
+        opener: Position,
+        opener_width: usize,
+    },
+    Unopened,
+    UnknownToken,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub position: Position,
+    pub found: String,
+    pub error: Error,
+    source: String,
+}
+
+impl Diagnostic {
+    // compiler style, the opener is underlined with '-' and the error with '^'
+    pub fn render(&self) -> String {
+        let number = self.position.line.to_string();
+        let gutter = " ".repeat(number.len());
+
+        let mut marks = vec![' '; self.source.chars().count() + 1];
+
+        if let Error::Mismatched {
+            opener,
+            opener_width,
+            ..
+        } = &self.error
+        {
+            mark(&mut marks, opener.column, *opener_width, '-');
+        }
+
+        mark(
+            &mut marks,
+            self.position.column,
+            self.found.chars().count(),
+            '^',
+        );
+
+        let marks: String = marks.into_iter().collect();
+
+        format!(
+            "error: {}\n{}--> {}:{}\n{} |\n{} | {}\n{} | {}",
+            self,
+            gutter,
+            self.position.line,
+            self.position.column,
+            gutter,
+            number,
+            self.source,
+            gutter,
+            marks.trim_end()
+        )
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.error {
+            Error::Mismatched { expected, .. } => {
+                write!(f, "expected `{}`, found `{}`", expected, self.found)
+            }
+            Error::Unopened => write!(f, "unexpected `{}`, nothing to close", self.found),
+            Error::UnknownToken => write!(f, "unknown token `{}`", self.found),
+        }
+    }
+}
+
+pub fn diagnose(grammar: &Grammar, input: &str) -> Vec<Diagnostic> {
+    input
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| diagnose_line(grammar, i + 1, line))
+        .collect()
+}
+
+fn diagnose_line(grammar: &Grammar, number: usize, line: &str) -> Option<Diagnostic> {
+    let position = |offset: usize| Position {
+        line: number,
+        column: line[..offset].chars().count() + 1,
+    };
+
+    let (offset, found, error) = match grammar.check(line) {
+        Parse::Incomplete(_) => return None,
+        Parse::Invalid(offset) => (
+            offset,
+            line[offset..].chars().next().unwrap().to_string(),
+            Error::UnknownToken,
+        ),
+        Parse::Corrupted {
+            found,
+            opener: None,
+        } => (found.offset, text(grammar, &found), Error::Unopened),
+        Parse::Corrupted {
+            found,
+            opener: Some(opener),
+        } => (
+            found.offset,
+            text(grammar, &found),
+            Error::Mismatched {
+                expected: grammar.pair(&opener).close.clone(),
+                opener: position(opener.offset),
+                opener_width: grammar.text(&opener).chars().count(),
+            },
+        ),
+    };
+
+    Some(Diagnostic {
+        position: position(offset),
+        found,
+        error,
+        source: line.to_string(),
+    })
+}
+
+fn text(grammar: &Grammar, token: &Token) -> String {
+    grammar.text(token).to_string()
+}
+
+fn mark(marks: &mut [char], column: usize, width: usize, c: char) {
+    for m in marks.iter_mut().skip(column - 1).take(width) {
+        *m = c;
+    }
+}
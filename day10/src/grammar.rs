@@ -0,0 +1,337 @@
+const STANDARD: &str = "( ) 3 1
+    [ ] 57 2
+    { } 1197 3
+    < > 25137 4";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pair {
+    pub open: String,
+    pub close: String,
+    pub miss_score: u64,
+    pub completion_score: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+    pub pair: usize,
+    pub open: bool,
+    pub offset: usize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Parse {
+    Incomplete(Vec<Token>),
+    Corrupted { found: Token, opener: Option<Token> },
+    Invalid(usize),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Repair {
+    pub text: String,
+    pub edits: usize,
+}
+
+pub struct Grammar {
+    pairs: Vec<Pair>,
+}
+
+impl Grammar {
+    pub fn standard() -> Self {
+        Self::parse(STANDARD)
+    }
+
+    // every line holds an opening and closing token with their miss
+    // and completion scores, separated by whitespace
+    pub fn parse(spec: &str) -> Self {
+        let pairs = spec
+            .lines()
+            .map(|l| l.split_whitespace().collect::<Vec<&str>>())
+            .filter(|parts| !parts.is_empty())
+            .map(|parts| Pair {
+                open: parts[0].to_string(),
+                close: parts[1].to_string(),
+                miss_score: parts[2].parse::<u64>().unwrap(),
+                completion_score: parts[3].parse::<u64>().unwrap(),
+            })
+            .collect();
+
+        Grammar { pairs }
+    }
+
+    pub fn pair(&self, token: &Token) -> &Pair {
+        &self.pairs[token.pair]
+    }
+
+    pub fn text(&self, token: &Token) -> &str {
+        let pair = self.pair(token);
+
+        if token.open {
+            &pair.open
+        } else {
+            &pair.close
+        }
+    }
+
+    // longest token wins, whitespace between tokens is skipped,
+    // error holds the offset of the first unknown input
+    pub fn tokenize(&self, line: &str) -> Result<Vec<Token>, usize> {
+        let mut tokens = Vec::new();
+        let mut offset = 0;
+
+        while offset < line.len() {
+            let rest = &line[offset..];
+            let trimmed = rest.trim_start();
+
+            if trimmed.len() != rest.len() {
+                offset += rest.len() - trimmed.len();
+                continue;
+            }
+
+            let token = self
+                .pairs
+                .iter()
+                .enumerate()
+                .flat_map(|(i, p)| [(i, true, &p.open), (i, false, &p.close)])
+                .filter(|(_, _, text)| rest.starts_with(&text[..]))
+                .max_by_key(|(_, _, text)| text.len())
+                .ok_or(offset)?;
+
+            tokens.push(Token {
+                pair: token.0,
+                open: token.1,
+                offset,
+            });
+            offset += token.2.len();
+        }
+
+        Ok(tokens)
+    }
+
+    pub fn check(&self, line: &str) -> Parse {
+        let tokens = match self.tokenize(line) {
+            Ok(tokens) => tokens,
+            Err(offset) => return Parse::Invalid(offset),
+        };
+
+        let mut stack: Vec<Token> = Vec::new();
+
+        for token in tokens {
+            if token.open {
+                stack.push(token);
+            } else {
+                match stack.pop() {
+                    Some(top) if top.pair == token.pair => {}
+                    opener => {
+                        return Parse::Corrupted {
+                            found: token,
+                            opener,
+                        }
+                    }
+                }
+            }
+        }
+
+        Parse::Incomplete(stack)
+    }
+
+    pub fn get_completion(&self, stack: &[Token]) -> Vec<Token> {
+        stack
+            .iter()
+            .rev()
+            .map(|t| Token {
+                pair: t.pair,
+                open: false,
+                offset: 0,
+            })
+            .collect()
+    }
+
+    pub fn get_completion_score(&self, stack: &[Token]) -> u64 {
+        self.get_completion(stack)
+            .iter()
+            .fold(0, |sum, t| sum * 5 + self.pair(t).completion_score)
+    }
+
+    // Corruption is fixed by the fewest inserted, deleted or replaced tokens
+    // which make the line a valid prefix, the rest is then completed.
+    pub fn repair(&self, line: &str) -> Result<Repair, usize> {
+        let tokens = self.tokenize(line)?;
+        let (edits, mut repaired) = Repairer::new(&tokens).run();
+
+        let mut stack = Vec::new();
+
+        for token in repaired.iter() {
+            if token.open {
+                stack.push(*token);
+            } else {
+                stack.pop();
+            }
+        }
+
+        repaired.extend(self.get_completion(&stack));
+
+        let separator = if self
+            .pairs
+            .iter()
+            .all(|p| p.open.len() == 1 && p.close.len() == 1)
+        {
+            ""
+        } else {
+            " "
+        };
+
+        let text = repaired
+            .iter()
+            .map(|t| self.text(t))
+            .collect::<Vec<&str>>()
+            .join(separator);
+
+        Ok(Repair { text, edits })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Choice {
+    Empty,
+    Pair(usize),
+    CloseAtEnd,
+    Delete,
+    Unmatched,
+    Block(usize),
+}
+
+// balanced[i][j] is the cheapest way to turn tokens i..j into a balanced
+// sequence, prefix[i] the cheapest way to turn tokens i.. into a valid prefix
+struct Repairer<'a> {
+    tokens: &'a [Token],
+    balanced: Vec<Vec<(usize, Choice)>>,
+    prefix: Vec<(usize, Choice)>,
+}
+
+impl<'a> Repairer<'a> {
+    fn new(tokens: &'a [Token]) -> Self {
+        let n = tokens.len();
+
+        Repairer {
+            tokens,
+            balanced: vec![vec![(0, Choice::Empty); n + 1]; n + 1],
+            prefix: vec![(0, Choice::Empty); n + 1],
+        }
+    }
+
+    fn run(mut self) -> (usize, Vec<Token>) {
+        let n = self.tokens.len();
+
+        for len in 1..=n {
+            for i in 0..=(n - len) {
+                self.balanced[i][i + len] = self.get_balanced(i, i + len);
+            }
+        }
+
+        for i in (0..n).rev() {
+            self.prefix[i] = self.get_prefix(i);
+        }
+
+        let mut output = Vec::new();
+        self.build_prefix(0, &mut output);
+
+        (self.prefix[0].0, output)
+    }
+
+    fn get_balanced(&self, i: usize, j: usize) -> (usize, Choice) {
+        let mut best = (1 + self.balanced[i + 1][j].0, Choice::Delete);
+
+        if self.tokens[i].open {
+            best = (1 + self.balanced[i + 1][j].0, Choice::CloseAtEnd);
+        }
+
+        for k in (i + 1)..j {
+            let substitutions = self.get_substitutions(i, k);
+            let cost = substitutions + self.balanced[i + 1][k].0 + self.balanced[k + 1][j].0;
+
+            if cost < best.0 || (cost == best.0 && substitutions == 0) {
+                best = (cost, Choice::Pair(k));
+            }
+        }
+
+        best
+    }
+
+    fn get_prefix(&self, i: usize) -> (usize, Choice) {
+        let mut best = (usize::MAX, Choice::Empty);
+
+        if self.tokens[i].open {
+            best = (self.prefix[i + 1].0, Choice::Unmatched);
+        }
+
+        for k in (i + 1)..=self.tokens.len() {
+            let cost = self.balanced[i][k].0 + self.prefix[k].0;
+
+            if cost < best.0 {
+                best = (cost, Choice::Block(k));
+            }
+        }
+
+        best
+    }
+
+    fn get_substitutions(&self, i: usize, k: usize) -> usize {
+        let (a, b) = (self.tokens[i], self.tokens[k]);
+
+        match (a.open, b.open) {
+            (true, false) if a.pair == b.pair => 0,
+            (true, _) | (false, false) => 1,
+            (false, true) => 2,
+        }
+    }
+
+    fn build_prefix(&self, i: usize, output: &mut Vec<Token>) {
+        match self.prefix[i].1 {
+            Choice::Unmatched => {
+                output.push(self.tokens[i]);
+                self.build_prefix(i + 1, output);
+            }
+            Choice::Block(k) => {
+                self.build_balanced(i, k, output);
+                self.build_prefix(k, output);
+            }
+            _ => {}
+        }
+    }
+
+    fn build_balanced(&self, i: usize, j: usize, output: &mut Vec<Token>) {
+        match self.balanced[i][j].1 {
+            Choice::Delete => self.build_balanced(i + 1, j, output),
+            Choice::CloseAtEnd => {
+                output.push(self.tokens[i]);
+                self.build_balanced(i + 1, j, output);
+                output.push(Token {
+                    open: false,
+                    ..self.tokens[i]
+                });
+            }
+            Choice::Pair(k) => {
+                // a closing token on the left takes the pair of the right one
+                let pair = if self.tokens[i].open {
+                    self.tokens[i].pair
+                } else {
+                    self.tokens[k].pair
+                };
+
+                output.push(Token {
+                    pair,
+                    open: true,
+                    ..self.tokens[i]
+                });
+                self.build_balanced(i + 1, k, output);
+                output.push(Token {
+                    pair,
+                    open: false,
+                    ..self.tokens[k]
+                });
+                self.build_balanced(k + 1, j, output);
+            }
+            _ => {}
+        }
+    }
+}
@@ -1,7 +1,39 @@
-use std::fs;
+mod diagnostic;
+mod grammar;
+
+use std::{env, fs};
+
+use diagnostic::diagnose;
+use grammar::{Grammar, Parse};
 
 fn main() {
     let input = fs::read_to_string("./_data/input.txt").expect("oh noes");
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    // repair|diagnose [grammar path]
+    if let Some(mode) = args.first() {
+        let grammar = match args.get(1) {
+            Some(path) => Grammar::parse(&fs::read_to_string(path).expect("oh noes")),
+            None => Grammar::standard(),
+        };
+
+        match mode.as_str() {
+            "repair" => {
+                for line in input.trim().lines().map(|l| l.trim()) {
+                    match grammar.repair(line) {
+                        Ok(repair) => println!("{} ({} edits)", repair.text, repair.edits),
+                        Err(offset) => println!("Unknown token at {}", offset),
+                    }
+                }
+            }
+            "diagnose" => {
+                for diagnostic in diagnose(&grammar, &input) {
+                    println!("{}\n", diagnostic.render());
+                }
+            }
+            _ => {}
+        }
+    }
 
     let output = process_data(input.clone());
     let adv_output = process_data_adv(input);
@@ -11,85 +43,39 @@ fn main() {
 }
 
 fn process_data(input: String) -> String {
+    get_syntax_score(&Grammar::standard(), &input).to_string()
+}
+
+fn process_data_adv(input: String) -> String {
+    get_completion_score(&Grammar::standard(), &input).to_string()
+}
+
+fn get_syntax_score(grammar: &Grammar, input: &str) -> u64 {
     input
         .trim()
         .lines()
-        .map(|l| l.trim())
-        .map(try_parse_line)
-        .filter_map(|r| r.err().map(get_miss_score))
-        .sum::<u32>()
-        .to_string()
+        .map(|l| grammar.check(l.trim()))
+        .filter_map(|p| match p {
+            Parse::Corrupted { found, .. } => Some(grammar.pair(&found).miss_score),
+            _ => None,
+        })
+        .sum()
 }
 
-fn process_data_adv(input: String) -> String {
+fn get_completion_score(grammar: &Grammar, input: &str) -> u64 {
     let mut scores: Vec<u64> = input
         .trim()
         .lines()
-        .map(|l| l.trim())
-        .map(try_parse_line)
-        .filter_map(|r| r.ok())
-        .map(|s| {
-            s.iter()
-                .rev()
-                .fold(0u64, |sum, &c| sum * 5 + get_compl_score(c) as u64)
+        .map(|l| grammar.check(l.trim()))
+        .filter_map(|p| match p {
+            Parse::Incomplete(stack) => Some(grammar.get_completion_score(&stack)),
+            _ => None,
         })
         .collect();
 
     scores.sort_unstable();
 
-    scores.get(scores.len() / 2).unwrap().to_string()
-}
-
-fn try_parse_line(input: &str) -> Result<Vec<char>, char> {
-    let mut stack = Vec::new();
-
-    for c in input.chars() {
-        if is_open(c) {
-            stack.push(c)
-        } else if let Some(top) = stack.pop() {
-            if top != get_open(c) {
-                return Err(c);
-            }
-        } else {
-            return Err(c);
-        }
-    }
-
-    Ok(stack)
-}
-
-fn get_miss_score(bracket: char) -> u32 {
-    match bracket {
-        ')' => 3,
-        ']' => 57,
-        '}' => 1197,
-        '>' => 25137,
-        _ => 0,
-    }
-}
-
-fn get_compl_score(bracket: char) -> u32 {
-    match bracket {
-        ')' | '(' => 1,
-        ']' | '[' => 2,
-        '}' | '{' => 3,
-        '>' | '<' => 4,
-        _ => 0,
-    }
-}
-
-fn is_open(bracket: char) -> bool {
-    matches!(bracket, '(' | '[' | '{' | '<')
-}
-
-fn get_open(close: char) -> char {
-    match close {
-        ')' => '(',
-        ']' => '[',
-        '}' => '{',
-        '>' => '<',
-        _ => panic!("Invalid bracket: {}", close),
-    }
+    *scores.get(scores.len() / 2).unwrap()
 }
 
 #[cfg(test)]
@@ -117,4 +103,91 @@ mod tests {
     fn adv_check() {
         assert_eq!("288957", process_data_adv(TEST_CASE.to_string()));
     }
+
+    #[test]
+    fn custom_grammar_check() {
+        let grammar = Grammar::parse("begin end 5 1\n( ) 3 2");
+        let input = "begin ( ) begin end
+            begin ( end
+            begin end end";
+
+        assert_eq!(5 + 5, get_syntax_score(&grammar, input));
+        assert_eq!(Err(6), grammar.tokenize("begin x"));
+        assert_eq!(
+            "begin ( ) begin end end",
+            grammar.repair("begin ( ) begin end").unwrap().text
+        );
+    }
+
+    #[test]
+    fn repair_check() {
+        let grammar = Grammar::standard();
+        let repairs: Vec<_> = TEST_CASE
+            .trim()
+            .lines()
+            .map(|l| grammar.repair(l.trim()).unwrap())
+            .collect();
+
+        assert_eq!("[({(<(())[]>[[{[]{<()<>>}}]])})]", repairs[0].text);
+        assert_eq!(0, repairs[0].edits);
+
+        for repair in repairs.iter() {
+            assert_eq!(Parse::Incomplete(vec![]), grammar.check(&repair.text));
+        }
+
+        assert_eq!(
+            vec![0, 0, 1, 0, 1, 1, 0, 1, 1, 0],
+            repairs.iter().map(|r| r.edits).collect::<Vec<usize>>()
+        );
+        assert_eq!(Ok(1), grammar.repair("(]").map(|r| r.edits));
+        assert_eq!("()", grammar.repair(")(").unwrap().text);
+    }
+
+    #[test]
+    fn diagnostic_check() {
+        let diagnostics = diagnose(&Grammar::standard(), TEST_CASE);
+
+        assert_eq!(5, diagnostics.len());
+        assert_eq!(
+            diagnostic::Position {
+                line: 3,
+                column: 21
+            },
+            diagnostics[0].position
+        );
+        assert_eq!(
+            "error: expected `]`, found `}`
+ --> 3:21
+  |
+3 |         {([(<{}[<>[]}>{[]{[(<()>
+  |                -    ^",
+            diagnostics[0].render()
+        );
+    }
+
+    #[test]
+    fn diagnostic_kinds_check() {
+        let grammar = Grammar::parse("begin end 5 1\n( ) 3 2");
+        let diagnostics = diagnose(&grammar, "begin ( end\n( ) end\n(x");
+
+        assert_eq!(
+            vec![
+                "expected `)`, found `end`",
+                "unexpected `end`, nothing to close",
+                "unknown token `x`"
+            ],
+            diagnostics
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<String>>()
+        );
+        assert_eq!(
+            "error: expected `)`, found `end`
+ --> 1:9
+  |
+1 | begin ( end
+  |       - ^^^",
+            diagnostics[0].render()
+        );
+    }
 }
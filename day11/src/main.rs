@@ -1,7 +1,39 @@
-use std::{collections::BTreeSet, fs};
+mod simulation;
+
+use std::{env, fmt, fs};
+
+use simulation::Simulation;
 
 fn main() {
     let input = fs::read_to_string("./_data/input.txt").expect("oh noes");
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if !args.is_empty() {
+        match parse_grid(input.clone(), &get_options(&args)) {
+            Ok(grid) => {
+                let steps = args
+                    .iter()
+                    .find_map(|a| a.strip_prefix("--steps="))
+                    .map(|s| s.parse::<u64>().unwrap())
+                    .unwrap_or(100);
+
+                let mut simulation = Simulation::new(grid);
+                println!("Custom result is: {}", simulation.total_flashes(steps));
+
+                match simulation.first_all_flash() {
+                    Some(step) => println!("Custom adv result is: {}", step),
+                    None => println!("Custom adv result is: never"),
+                }
+
+                let cycle = simulation.detect_cycle();
+                println!(
+                    "Cycle starts at {} with period {}",
+                    cycle.start, cycle.period
+                );
+            }
+            Err(e) => println!("{}", e),
+        }
+    }
 
     let output = process_data(input.clone());
     let adv_output = process_data_adv(input);
@@ -11,123 +43,211 @@ fn main() {
 }
 
 fn process_data(input: String) -> String {
-    let mut grid = parse(input);
+    let mut grid = get_grid(input);
 
-    (0..SIZE)
-        .map(|_| step(&mut grid))
-        .sum::<usize>()
-        .to_string()
+    (0..100).map(|_| step(&mut grid)).sum::<usize>().to_string()
 }
 
 fn process_data_adv(input: String) -> String {
-    let mut grid = parse(input);
+    let mut grid = get_grid(input);
     let mut step_count = 0u32;
 
     loop {
         step_count += 1;
 
-        if step(&mut grid) == SIZE {
+        if step(&mut grid) == grid.energy.len() {
             return step_count.to_string();
         }
     }
 }
 
-const DIMENSION: usize = 10;
-const SIZE: usize = DIMENSION * DIMENSION;
-
-fn parse(input: String) -> Vec<u32> {
-    input
-        .trim()
-        .lines()
-        .map(|l| l.trim())
-        .flat_map(|l| l.chars())
-        .map(|c| c.to_digit(10).unwrap())
-        .collect()
+// --wrap, --threshold=N and --increment=N shape the grid,
+// --steps=N is read by main and anything unknown is ignored
+fn get_options(args: &[String]) -> Options {
+    let mut options = Options::default();
+
+    for arg in args {
+        if arg == "--wrap" {
+            options.wrap = true;
+        } else if let Some(threshold) = arg.strip_prefix("--threshold=") {
+            options.threshold = threshold.parse().unwrap();
+        } else if let Some(increment) = arg.strip_prefix("--increment=") {
+            options.increment = increment.parse().unwrap();
+        }
+    }
+
+    options
+}
+
+fn get_grid(input: String) -> Grid {
+    parse_grid(input, &Options::default()).unwrap()
+}
+
+// As with the day 9 height maps the widest line sets the width, but
+// octopuses cannot be padded in so every line has to be that wide.
+fn parse_grid(input: String, options: &Options) -> Result<Grid, GridError> {
+    let lines: Vec<&str> = input.trim().lines().map(str::trim).collect();
+    let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+
+    if width == 0 {
+        return Err(GridError::NoOctopuses);
+    }
+
+    let mut energy = Vec::with_capacity(width * lines.len());
+
+    for (y, line) in lines.iter().enumerate() {
+        let count = line.chars().count();
+
+        if count != width {
+            return Err(GridError::ShortLine { y, count, width });
+        }
+
+        for (x, c) in line.chars().enumerate() {
+            energy.push(c.to_digit(10).ok_or(GridError::NotADigit { x, y, c })?);
+        }
+    }
+
+    Ok(Grid {
+        width,
+        height: lines.len(),
+        energy,
+        wrap: options.wrap,
+        threshold: options.threshold,
+        increment: options.increment,
+    })
+}
+
+fn step(grid: &mut Grid) -> usize {
+    step_waves(grid).iter().map(|w| w.len()).sum()
 }
 
-fn step(grid: &mut Vec<u32>) -> usize {
-    for v in grid.iter_mut() {
-        if *v > 9 {
+// every wave holds the cells pushed past the threshold by the previous one
+fn step_waves(grid: &mut Grid) -> Vec<Vec<usize>> {
+    for v in grid.energy.iter_mut() {
+        if *v > grid.threshold {
             *v = 0;
         }
 
-        *v += 1
+        *v += grid.increment
     }
 
-    let mut flashed = BTreeSet::new();
-    let mut to_do: Vec<usize> = grid
+    let mut waves = Vec::new();
+    let mut wave: Vec<usize> = grid
+        .energy
         .iter()
         .enumerate()
-        .filter_map(|(i, v)| {
-            if *v > 9 {
-                flashed.insert(i);
-                Some(i)
-            } else {
-                None
-            }
-        })
+        .filter(|(_, v)| **v > grid.threshold)
+        .map(|(i, _)| i)
         .collect();
 
-    while let Some(next) = to_do.pop() {
-        let neighs = get_neighbor_positions(next);
+    while !wave.is_empty() {
+        let mut next_wave = Vec::new();
 
-        for &n in neighs.iter() {
-            grid[n] += 1;
+        for &position in wave.iter() {
+            for n in get_neighbor_positions(position, grid) {
+                grid.energy[n] += 1;
 
-            if grid[n] > 9 && !flashed.contains(&n) {
-                to_do.push(n);
-                flashed.insert(n);
+                // energy crosses the threshold exactly once per step
+                if grid.energy[n] == grid.threshold + 1 {
+                    next_wave.push(n);
+                }
             }
         }
+
+        next_wave.sort_unstable();
+        waves.push(wave);
+        wave = next_wave;
     }
 
-    flashed.len()
+    waves
 }
 
-fn get_neighbor_positions(position: usize) -> Vec<usize> {
+// on a wrapping grid cells reachable through several edges count once
+fn get_neighbor_positions(position: usize, grid: &Grid) -> Vec<usize> {
+    let (width, height) = (grid.width as isize, grid.height as isize);
+    let x = position as isize % width;
+    let y = position as isize / width;
+
     let mut neighs = Vec::with_capacity(8);
-    let is_not_left = position % DIMENSION != 0;
-    let is_not_right = position % DIMENSION != (DIMENSION - 1);
-    let is_not_top = position >= DIMENSION;
-    let is_not_bottom = position < SIZE - DIMENSION;
 
-    if is_not_top {
-        let above = position - DIMENSION;
+    for dy in -1..=1 {
+        for dx in -1..=1 {
+            let (mut nx, mut ny) = (x + dx, y + dy);
 
-        neighs.push(above);
+            if grid.wrap {
+                nx = nx.rem_euclid(width);
+                ny = ny.rem_euclid(height);
+            } else if nx < 0 || ny < 0 || nx >= width || ny >= height {
+                continue;
+            }
 
-        if is_not_left {
-            neighs.push(above - 1);
-        }
+            let n = (ny * width + nx) as usize;
 
-        if is_not_right {
-            neighs.push(above + 1);
+            if n != position && !neighs.contains(&n) {
+                neighs.push(n);
+            }
         }
     }
 
-    if is_not_left {
-        neighs.push(position - 1);
-    }
-
-    if is_not_right {
-        neighs.push(position + 1);
-    }
-
-    if is_not_bottom {
-        let below = position + DIMENSION;
+    neighs
+}
 
-        neighs.push(below);
+struct Options {
+    wrap: bool,
+    threshold: u32,
+    increment: u32,
+}
 
-        if is_not_left {
-            neighs.push(below - 1);
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            wrap: false,
+            threshold: 9,
+            increment: 1,
         }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum GridError {
+    NoOctopuses,
+    NotADigit {
+        x: usize,
+        y: usize,
+        c: char,
+    },
+    ShortLine {
+        y: usize,
+        count: usize,
+        width: usize,
+    },
+}
 
-        if is_not_right {
-            neighs.push(below + 1);
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GridError::NoOctopuses => write!(f, "No octopuses in the grid"),
+            GridError::NotADigit { x, y, c } => {
+                write!(f, "Energy of the octopus at {},{} is '{}'", x, y, c)
+            }
+            GridError::ShortLine { y, count, width } => write!(
+                f,
+                "Line {} holds {} octopuses but the grid is {} wide",
+                y, count, width
+            ),
         }
     }
+}
 
-    neighs
+// octopuses flash once their energy exceeds the threshold,
+// every step raises the energy of all of them by the increment
+struct Grid {
+    width: usize,
+    height: usize,
+    energy: Vec<u32>,
+    wrap: bool,
+    threshold: u32,
+    increment: u32,
 }
 
 #[cfg(test)]
@@ -155,4 +275,137 @@ mod tests {
     fn adv_check() {
         assert_eq!("195", process_data_adv(TEST_CASE.to_string()));
     }
+
+    #[test]
+    fn rectangular_check() {
+        let mut grid = get_grid("11111\n19991\n19191\n19991\n11111".to_string());
+
+        assert_eq!((5, 5), (grid.width, grid.height));
+        assert_eq!(9, step(&mut grid));
+        assert_eq!(0, step(&mut grid));
+
+        let grid = get_grid("123\n456".to_string());
+
+        assert_eq!((3, 2), (grid.width, grid.height));
+        assert_eq!(vec![0, 2, 3, 4, 5], get_neighbor_positions(1, &grid));
+    }
+
+    #[test]
+    fn wrap_check() {
+        let options = Options {
+            wrap: true,
+            ..Options::default()
+        };
+        let grid = parse_grid("1234\n5678\n9123".to_string(), &options).unwrap();
+
+        assert_eq!(
+            vec![11, 8, 9, 3, 1, 7, 4, 5],
+            get_neighbor_positions(0, &grid)
+        );
+
+        let mut grid = parse_grid("900\n000".to_string(), &options).unwrap();
+
+        assert_eq!(1, step(&mut grid));
+        assert_eq!(vec![10, 2, 2, 2, 2, 2], grid.energy);
+    }
+
+    #[test]
+    fn options_check() {
+        let options = Options {
+            threshold: 3,
+            increment: 2,
+            ..Options::default()
+        };
+        let mut grid = parse_grid("20\n00".to_string(), &options).unwrap();
+
+        assert_eq!(1, step(&mut grid));
+        assert_eq!(vec![4, 3, 3, 3], grid.energy);
+        assert_eq!(4, step(&mut grid));
+    }
+
+    #[test]
+    fn invalid_grid_check() {
+        let options = Options::default();
+
+        assert_eq!(
+            Err(GridError::ShortLine {
+                y: 1,
+                count: 2,
+                width: 3
+            }),
+            parse_grid("123\n45".to_string(), &options).map(|_| ())
+        );
+        assert_eq!(
+            Err(GridError::ShortLine {
+                y: 0,
+                count: 2,
+                width: 3
+            }),
+            parse_grid("12\n345".to_string(), &options).map(|_| ())
+        );
+        assert_eq!(
+            Err(GridError::NotADigit { x: 1, y: 0, c: 'x' }),
+            parse_grid("1x3".to_string(), &options).map(|_| ())
+        );
+        assert_eq!(
+            Err(GridError::NoOctopuses),
+            parse_grid("".to_string(), &options).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn waves_check() {
+        let mut grid = get_grid("11111\n19991\n19191\n19991\n11111".to_string());
+        let mut simulation = Simulation::new(get_grid(TEST_CASE.to_string()));
+
+        assert_eq!(
+            vec![vec![6, 7, 8, 11, 13, 16, 17, 18], vec![12]],
+            step_waves(&mut grid)
+        );
+        assert_eq!(0, simulation.next().unwrap().flashes());
+
+        let record = simulation.next().unwrap();
+
+        assert_eq!(2, record.step);
+        assert_eq!(35, record.flashes());
+        assert_eq!(13, record.waves[0].len());
+    }
+
+    #[test]
+    fn cycle_check() {
+        let mut simulation = Simulation::new(get_grid(TEST_CASE.to_string()));
+
+        assert_eq!(Some(195), simulation.first_all_flash());
+        assert_eq!(
+            simulation::Cycle {
+                start: 195,
+                period: 10
+            },
+            simulation.detect_cycle()
+        );
+        assert_eq!(1656, simulation.total_flashes(100));
+
+        let mut grid = get_grid(TEST_CASE.to_string());
+        let naive: usize = (0..1000).map(|_| step(&mut grid)).sum();
+
+        assert_eq!(naive as u64, simulation.total_flashes(1000));
+        assert_eq!(
+            simulation.total_flashes(195) + 100 * 100_000_000,
+            simulation.total_flashes(195 + 1_000_000_000)
+        );
+    }
+
+    #[test]
+    fn never_all_flash_check() {
+        let mut simulation = Simulation::new(get_grid("1234".to_string()));
+
+        assert_eq!(None, simulation.first_all_flash());
+        assert_eq!(
+            simulation::Cycle {
+                start: 15,
+                period: 9
+            },
+            simulation.detect_cycle()
+        );
+    }
 }
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use crate::{step_waves, Grid};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct StepRecord {
+    pub step: u64,
+    // cells flashing because of the previous wave, the first wave
+    // holds the cells charged past the threshold by the step itself
+    pub waves: Vec<Vec<usize>>,
+}
+
+impl StepRecord {
+    pub fn flashes(&self) -> usize {
+        self.waves.iter().map(|w| w.len()).sum()
+    }
+}
+
+// the state after step start repeats after every period steps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: u64,
+    pub period: u64,
+}
+
+pub struct Simulation {
+    grid: Grid,
+    states: HashMap<Vec<u32>, u64>,
+    // total flashes after each step, starting with no steps
+    flashes: Vec<u64>,
+    cycle: Option<Cycle>,
+}
+
+impl Simulation {
+    pub fn new(grid: Grid) -> Self {
+        let mut simulation = Simulation {
+            grid,
+            states: HashMap::new(),
+            flashes: vec![0],
+            cycle: None,
+        };
+
+        let state = simulation.get_state();
+        simulation.states.insert(state, 0);
+
+        simulation
+    }
+
+    // runs until some state repeats, which may take long on
+    // grids that never synchronize
+    pub fn detect_cycle(&mut self) -> Cycle {
+        while self.cycle.is_none() {
+            self.next();
+        }
+
+        self.cycle.unwrap()
+    }
+
+    pub fn total_flashes(&mut self, steps: u64) -> u64 {
+        while self.steps() < steps && self.cycle.is_none() {
+            self.next();
+        }
+
+        match self.cycle {
+            Some(Cycle { start, period }) if steps > self.steps() => {
+                let (full, rest) = ((steps - start) / period, (steps - start) % period);
+                let per_cycle = self.get_flashes(start + period) - self.get_flashes(start);
+
+                self.get_flashes(start) + full * per_cycle + self.get_flashes(start + rest)
+                    - self.get_flashes(start)
+            }
+            _ => self.get_flashes(steps),
+        }
+    }
+
+    pub fn first_all_flash(&mut self) -> Option<u64> {
+        let size = self.grid.energy.len() as u64;
+        let mut checked = 0;
+
+        loop {
+            while checked < self.steps() {
+                checked += 1;
+
+                if self.get_flashes(checked) - self.get_flashes(checked - 1) == size {
+                    return Some(checked);
+                }
+            }
+
+            if self.cycle.is_some() {
+                return None;
+            }
+
+            self.next();
+        }
+    }
+
+    fn steps(&self) -> u64 {
+        self.flashes.len() as u64 - 1
+    }
+
+    fn get_flashes(&self, step: u64) -> u64 {
+        self.flashes[step as usize]
+    }
+
+    // cells past the threshold are reset by the next step, so they
+    // all behave like empty ones
+    fn get_state(&self) -> Vec<u32> {
+        self.grid
+            .energy
+            .iter()
+            .map(|&v| if v > self.grid.threshold { 0 } else { v })
+            .collect()
+    }
+}
+
+impl Iterator for Simulation {
+    type Item = StepRecord;
+
+    fn next(&mut self) -> Option<StepRecord> {
+        let waves = step_waves(&mut self.grid);
+        let step = self.steps() + 1;
+
+        let record = StepRecord { step, waves };
+        self.flashes
+            .push(self.get_flashes(step - 1) + record.flashes() as u64);
+
+        if self.cycle.is_none() {
+            let state = self.get_state();
+
+            if let Some(start) = self.states.insert(state, step) {
+                self.cycle = Some(Cycle {
+                    start,
+                    period: step - start,
+                });
+                self.states.clear();
+            }
+        }
+
+        Some(record)
+    }
+}
@@ -0,0 +1,131 @@
+use std::{collections::HashMap, fmt};
+
+use crate::{policy::VisitPolicy, Graph};
+
+// visits of a limited cave are counted in its own bits of the state
+#[derive(Debug, Clone, Copy)]
+struct Counter {
+    shift: u32,
+    mask: u64,
+    limit: u64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct TooManyLimits {
+    pub bits: u32,
+}
+
+impl fmt::Display for TooManyLimits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Visit counters of limited caves need {} bits, only {} fit",
+            self.bits,
+            u64::BITS
+        )
+    }
+}
+
+// caves are interned to ids, limited ones additionally own a counter
+// packed into the visited state
+pub struct CaveIndex {
+    neighbors: Vec<Vec<usize>>,
+    counters: Vec<Option<Counter>>,
+    repeatable: Vec<bool>,
+    start: Option<usize>,
+    end: Option<usize>,
+    repeats: u32,
+}
+
+impl CaveIndex {
+    pub fn new(graph: &Graph, policy: &VisitPolicy) -> Result<Self, TooManyLimits> {
+        let names: Vec<&String> = graph.keys().collect();
+        let id = |name: &str| names.iter().position(|n| *n == name);
+
+        let mut shift = 0;
+        let counters: Vec<Option<Counter>> = names
+            .iter()
+            .map(|name| {
+                policy.limit(name).map(|limit| {
+                    let limit = limit as u64;
+                    let counter = Counter {
+                        shift,
+                        mask: (limit + 1).next_power_of_two() - 1,
+                        limit,
+                    };
+
+                    shift += counter.mask.count_ones();
+
+                    counter
+                })
+            })
+            .collect();
+
+        if shift > u64::BITS {
+            return Err(TooManyLimits { bits: shift });
+        }
+
+        let neighbors = graph
+            .values()
+            .map(|nodes| nodes.iter().map(|n| id(&n.name).unwrap()).collect())
+            .collect();
+
+        Ok(CaveIndex {
+            neighbors,
+            counters,
+            repeatable: names.iter().map(|n| policy.is_repeatable(n)).collect(),
+            start: id(&policy.start),
+            end: id(&policy.end).filter(|_| policy.allows_entry(&policy.end)),
+            repeats: policy.repeats,
+        })
+    }
+}
+
+// never returns on a cycle of caves without limits
+pub fn count_paths(index: &CaveIndex) -> u64 {
+    let mut memo = HashMap::new();
+
+    match (index.start, index.end) {
+        (Some(start), Some(_)) => count_from(index, start, 0, index.repeats, &mut memo),
+        _ => 0,
+    }
+}
+
+fn count_from(
+    index: &CaveIndex,
+    cave: usize,
+    visited: u64,
+    repeats: u32,
+    memo: &mut HashMap<(usize, u64, u32), u64>,
+) -> u64 {
+    if Some(cave) == index.end {
+        return 1;
+    }
+
+    if let Some(&count) = memo.get(&(cave, visited, repeats)) {
+        return count;
+    }
+
+    let mut count = 0;
+
+    for &next in index.neighbors[cave].iter() {
+        if Some(next) == index.start {
+            continue;
+        }
+
+        count += match index.counters[next] {
+            Some(c) if visited.checked_shr(c.shift).unwrap_or(0) & c.mask < c.limit => {
+                count_from(index, next, visited + (1 << c.shift), repeats, memo)
+            }
+            Some(_) if repeats > 0 && index.repeatable[next] => {
+                count_from(index, next, visited, repeats - 1, memo)
+            }
+            Some(_) => 0,
+            None => count_from(index, next, visited, repeats, memo),
+        };
+    }
+
+    memo.insert((cave, visited, repeats), count);
+
+    count
+}
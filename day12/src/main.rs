@@ -1,10 +1,28 @@
-use std::{
-    collections::{BTreeMap, BTreeSet},
-    fs,
-};
+mod counting;
+mod paths;
+mod policy;
+
+use std::{collections::BTreeMap, env, fs};
+
+use counting::{count_paths, CaveIndex};
+use paths::Paths;
+use policy::VisitPolicy;
 
 fn main() {
     let input = fs::read_to_string("./_data/input.txt").expect("oh noes");
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if !args.is_empty() {
+        let graph = parse(input.clone());
+        let policy = get_policy(&args);
+
+        list_paths(&graph, &policy, &args);
+
+        match CaveIndex::new(&graph, &policy) {
+            Ok(index) => println!("Custom result is: {}", count_paths(&index)),
+            Err(e) => println!("{}", e),
+        }
+    }
 
     let output = process_data(input.clone());
     let adv_output = process_data_adv(input);
@@ -14,38 +32,84 @@ fn main() {
 }
 
 fn process_data(input: String) -> String {
-    let graph = parse(input);
-    let mut visited_small = BTreeSet::new();
-
-    traverse(START, &graph, &mut visited_small).to_string()
+    count_with_repeats(input, 0).to_string()
 }
 
 fn process_data_adv(input: String) -> String {
-    let graph = parse(input);
-    let mut visited_small = BTreeSet::new();
+    count_with_repeats(input, 1).to_string()
+}
 
-    traverse_with_repeat(START, &graph, &mut visited_small, false).to_string()
+fn count_with_repeats(input: String, repeats: u32) -> u64 {
+    let policy = VisitPolicy::with_repeats(repeats);
+    let index = CaveIndex::new(&parse(input), &policy).unwrap_or_else(|e| panic!("{}", e));
+
+    count_paths(&index)
 }
 
-const START: &str = "start";
-const END: &str = "end";
+// policy flags are --start=CAVE, --end=CAVE, --repeats=N,
+// --limit=CAVE:N and --forbid=CAVE
+fn get_policy(args: &[String]) -> VisitPolicy {
+    let mut policy = VisitPolicy::default();
+
+    for arg in args {
+        match arg.split_once('=') {
+            Some(("--start", start)) => policy.start = start.to_string(),
+            Some(("--end", end)) => policy.end = end.to_string(),
+            Some(("--repeats", repeats)) => policy.repeats = repeats.parse().unwrap(),
+            Some(("--limit", limit)) => {
+                let (cave, limit) = limit.split_once(':').unwrap();
+                policy
+                    .limits
+                    .insert(cave.to_string(), limit.parse().unwrap());
+            }
+            Some(("--forbid", cave)) => {
+                policy.forbidden.insert(cave.to_string());
+            }
+            _ => {}
+        }
+    }
+
+    policy
+}
+
+// flags are --through=CAVE, --shortest, --longest and --dot=PATH
+fn list_paths(graph: &Graph, policy: &VisitPolicy, args: &[String]) {
+    let through = args.iter().find_map(|a| a.strip_prefix("--through="));
+    let paths = Paths::new(graph, policy).filter(|p| through.is_none_or(|cave| p.contains(&cave)));
+
+    let selected = if args.iter().any(|a| a == "--shortest") {
+        paths::shortest(paths)
+    } else if args.iter().any(|a| a == "--longest") {
+        paths::longest(paths)
+    } else {
+        paths.collect()
+    };
+
+    for path in selected.iter() {
+        println!("{}", path.join(","));
+    }
 
-fn parse(input: String) -> BTreeMap<String, Vec<Node>> {
-    let mut res: BTreeMap<String, Vec<Node>> = BTreeMap::new();
+    if let Some(path) = args.iter().find_map(|a| a.strip_prefix("--dot=")) {
+        fs::write(path, paths::to_dot(graph, policy, &selected)).expect("oh noes");
+    }
+}
+
+type Graph = BTreeMap<String, Vec<Node>>;
+
+fn parse(input: String) -> Graph {
+    let mut res: Graph = BTreeMap::new();
 
     for line in input.trim().lines() {
         let nodes: Vec<&str> = line.trim().split('-').collect();
-        let first_name = *nodes.get(0).unwrap();
+        let first_name = *nodes.first().unwrap();
         let second_name = *nodes.get(1).unwrap();
 
         let first_node = Node {
             name: first_name.to_owned(),
-            is_small: is_all_lower(first_name),
         };
 
         let second_node = Node {
             name: second_name.to_owned(),
-            is_small: is_all_lower(second_name),
         };
 
         if let Some(first_nodes) = res.get_mut(first_name) {
@@ -64,88 +128,6 @@ fn parse(input: String) -> BTreeMap<String, Vec<Node>> {
     res
 }
 
-fn traverse(
-    from: &str,
-    graph: &BTreeMap<String, Vec<Node>>,
-    visited_small: &mut BTreeSet<String>,
-) -> u32 {
-    if let Some(paths) = graph.get(from) {
-        paths
-            .iter()
-            .filter(|p| p.name != START)
-            .map(|path| {
-                if path.name == END {
-                    return 1;
-                }
-
-                if path.is_small {
-                    if visited_small.contains(&path.name) {
-                        return 0;
-                    } else {
-                        visited_small.insert(path.name.to_owned());
-                    }
-                }
-
-                let end_count = traverse(&path.name, graph, visited_small);
-
-                if path.is_small {
-                    visited_small.remove(&path.name);
-                }
-
-                end_count
-            })
-            .sum::<u32>()
-    } else {
-        0
-    }
-}
-
-fn traverse_with_repeat(
-    from: &str,
-    graph: &BTreeMap<String, Vec<Node>>,
-    visited_small: &mut BTreeSet<String>,
-    repeated_once: bool,
-) -> u32 {
-    if let Some(paths) = graph.get(from) {
-        paths
-            .iter()
-            .filter(|p| p.name != START)
-            .map(|path| {
-                if path.name == END {
-                    return 1;
-                }
-
-                let mut added_repeat = false;
-
-                if path.is_small {
-                    if !visited_small.contains(&path.name) {
-                        visited_small.insert(path.name.to_owned());
-                    } else if repeated_once {
-                        return 0;
-                    } else {
-                        added_repeat = true;
-                    }
-                }
-
-                let end_count = traverse_with_repeat(
-                    &path.name,
-                    graph,
-                    visited_small,
-                    repeated_once || added_repeat,
-                );
-
-                if path.is_small && !added_repeat {
-                    visited_small.remove(&path.name);
-                }
-
-                end_count
-            })
-            .sum::<u32>()
-    } else {
-        0
-    }
-}
-
 fn is_all_lower(input: &str) -> bool {
     input.chars().all(|c| c.is_ascii_lowercase())
 }
@@ -153,11 +135,12 @@ fn is_all_lower(input: &str) -> bool {
 #[derive(Debug)]
 struct Node {
     name: String,
-    is_small: bool,
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeSet;
+
     use crate::*;
 
     const TEST_CASE_1: &str = "start-A
@@ -230,4 +213,237 @@ mod tests {
     fn adv_check_3() {
         assert_eq!("3509", process_data_adv(TEST_CASE_3.to_string()));
     }
+
+    #[test]
+    fn paths_check() {
+        let graph = parse(TEST_CASE_1.to_string());
+        let (once, twice) = (VisitPolicy::default(), VisitPolicy::with_repeats(1));
+
+        assert_eq!(10, Paths::new(&graph, &once).count());
+        assert_eq!(36, Paths::new(&graph, &twice).count());
+        assert_eq!(
+            Some(vec!["start", "A", "c", "A", "b", "A", "end"]),
+            Paths::new(&graph, &once).next()
+        );
+
+        for test_case in [TEST_CASE_2, TEST_CASE_3] {
+            let graph = parse(test_case.to_string());
+
+            assert_eq!(
+                count_paths(&CaveIndex::new(&graph, &twice).unwrap()) as usize,
+                Paths::new(&graph, &twice).count()
+            );
+        }
+    }
+
+    #[test]
+    fn filter_check() {
+        let graph = parse(TEST_CASE_1.to_string());
+        let (once, twice) = (VisitPolicy::default(), VisitPolicy::with_repeats(1));
+        let through_d = Paths::new(&graph, &twice).filter(|p| p.contains(&"d"));
+
+        assert_eq!(
+            vec![vec!["start", "A", "end"], vec!["start", "b", "end"]],
+            paths::shortest(Paths::new(&graph, &once))
+        );
+        assert_eq!(
+            vec![vec!["start", "b", "d", "b", "end"]],
+            paths::shortest(through_d)
+        );
+
+        let longest = paths::longest(Paths::new(&graph, &twice));
+
+        assert_eq!(8, longest.len());
+        assert!(longest.iter().all(|p| p.len() == 9));
+    }
+
+    #[test]
+    fn dot_check() {
+        let graph = parse(TEST_CASE_1.to_string());
+        let dot = paths::to_dot(
+            &graph,
+            &VisitPolicy::default(),
+            &[vec!["start", "b", "end"]],
+        );
+
+        assert!(dot.starts_with("graph caves {\n    \"A\" [shape=box];\n"));
+        assert!(dot.contains("    \"b\" [shape=ellipse];\n"));
+        assert!(dot.contains("    \"A\" -- \"b\";\n"));
+        assert!(dot.contains("    \"b\" -- \"end\" [color=red, penwidth=2];\n}"));
+        assert_eq!(7 + 6 + 2 + 2, dot.lines().count());
+    }
+
+    #[test]
+    fn counting_check() {
+        // a hub with many small caves hanging off it
+        let input: String = (0..12)
+            .map(|i| format!("HUB-c{}\n", (b'a' + i) as char))
+            .chain(["start-HUB\n".to_string(), "HUB-end".to_string()])
+            .collect();
+        let graph = parse(input);
+        let count = |repeats| {
+            count_paths(&CaveIndex::new(&graph, &VisitPolicy::with_repeats(repeats)).unwrap())
+        };
+
+        assert_eq!(1302061345, count(0));
+        assert!(count(2) > count(1));
+
+        let graph = parse(TEST_CASE_3.to_string());
+        let once = VisitPolicy::default();
+
+        assert_eq!(
+            Paths::new(&graph, &once).count() as u64,
+            count_paths(&CaveIndex::new(&graph, &once).unwrap())
+        );
+
+        // 65 small caves need one bit each
+        let input: String = (0..65)
+            .map(|i| {
+                format!(
+                    "HUB-c{}{}\n",
+                    (b'a' + i / 26) as char,
+                    (b'a' + i % 26) as char
+                )
+            })
+            .chain(["start-HUB\n".to_string(), "HUB-end".to_string()])
+            .collect();
+
+        assert!(matches!(
+            CaveIndex::new(&parse(input), &once),
+            Err(counting::TooManyLimits { bits: 67 })
+        ));
+    }
+
+    #[test]
+    fn brute_force_check() {
+        for test_case in [TEST_CASE_1, TEST_CASE_2, TEST_CASE_3] {
+            let graph = parse(test_case.to_string());
+            let count = |repeats| {
+                count_paths(&CaveIndex::new(&graph, &VisitPolicy::with_repeats(repeats)).unwrap())
+            };
+
+            assert_eq!(traverse("start", &graph, &mut BTreeSet::new()), count(0));
+            assert_eq!(
+                traverse_with_repeat("start", &graph, &mut BTreeSet::new(), false),
+                count(1)
+            );
+        }
+    }
+
+    // the original recursive counting, kept as an independent check
+    fn traverse(from: &str, graph: &Graph, visited_small: &mut BTreeSet<String>) -> u64 {
+        traverse_with_repeat(from, graph, visited_small, true)
+    }
+
+    fn traverse_with_repeat(
+        from: &str,
+        graph: &Graph,
+        visited_small: &mut BTreeSet<String>,
+        repeated_once: bool,
+    ) -> u64 {
+        if let Some(paths) = graph.get(from) {
+            paths
+                .iter()
+                .filter(|p| p.name != "start")
+                .map(|path| {
+                    if path.name == "end" {
+                        return 1;
+                    }
+
+                    let is_small = is_all_lower(&path.name);
+                    let mut added_repeat = false;
+
+                    if is_small {
+                        if !visited_small.contains(&path.name) {
+                            visited_small.insert(path.name.to_owned());
+                        } else if repeated_once {
+                            return 0;
+                        } else {
+                            added_repeat = true;
+                        }
+                    }
+
+                    let end_count = traverse_with_repeat(
+                        &path.name,
+                        graph,
+                        visited_small,
+                        repeated_once || added_repeat,
+                    );
+
+                    if is_small && !added_repeat {
+                        visited_small.remove(&path.name);
+                    }
+
+                    end_count
+                })
+                .sum::<u64>()
+        } else {
+            0
+        }
+    }
+
+    #[test]
+    fn policy_check() {
+        let graph = parse(TEST_CASE_2.to_string());
+        let check = |policy: VisitPolicy, expected: u64| {
+            assert_eq!(
+                expected,
+                count_paths(&CaveIndex::new(&graph, &policy).unwrap())
+            );
+            assert_eq!(expected as usize, Paths::new(&graph, &policy).count());
+        };
+
+        check(VisitPolicy::with_repeats(2), 449);
+        check(
+            VisitPolicy {
+                forbidden: BTreeSet::from(["dc".to_string()]),
+                ..VisitPolicy::default()
+            },
+            3,
+        );
+        check(
+            VisitPolicy {
+                limits: BTreeMap::from([("kj".to_string(), 2), ("HN".to_string(), 1)]),
+                ..VisitPolicy::default()
+            },
+            18,
+        );
+        check(
+            VisitPolicy {
+                start: "kj".to_string(),
+                end: "LN".to_string(),
+                ..VisitPolicy::default()
+            },
+            14,
+        );
+        check(
+            VisitPolicy {
+                start: "nowhere".to_string(),
+                ..VisitPolicy::default()
+            },
+            0,
+        );
+        check(
+            VisitPolicy {
+                forbidden: BTreeSet::from(["end".to_string()]),
+                ..VisitPolicy::default()
+            },
+            0,
+        );
+        check(
+            VisitPolicy {
+                limits: BTreeMap::from([("end".to_string(), 0)]),
+                ..VisitPolicy::default()
+            },
+            0,
+        );
+        check(
+            VisitPolicy {
+                end: "LN".to_string(),
+                limits: BTreeMap::from([("LN".to_string(), 0)]),
+                ..VisitPolicy::default()
+            },
+            0,
+        );
+    }
 }
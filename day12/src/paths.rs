@@ -0,0 +1,176 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{is_all_lower, policy::VisitPolicy, Graph};
+
+const COLORS: [&str; 6] = ["red", "blue", "green", "orange", "purple", "brown"];
+
+pub type Path<'a> = Vec<&'a str>;
+
+// depth first, a path is only built once the previous one was consumed
+pub struct Paths<'a> {
+    graph: &'a Graph,
+    policy: &'a VisitPolicy,
+    path: Path<'a>,
+    next: Vec<usize>,
+    repeated: Vec<bool>,
+    visits: BTreeMap<&'a str, u32>,
+}
+
+impl<'a> Paths<'a> {
+    pub fn new(graph: &'a Graph, policy: &'a VisitPolicy) -> Self {
+        let path = match graph.get_key_value(&policy.start) {
+            Some((start, _))
+                if graph.contains_key(&policy.end) && policy.allows_entry(&policy.end) =>
+            {
+                vec![start.as_str()]
+            }
+            _ => vec![],
+        };
+
+        Paths {
+            graph,
+            policy,
+            next: vec![0; path.len()],
+            repeated: vec![false; path.len()],
+            path,
+            visits: BTreeMap::new(),
+        }
+    }
+
+    fn push(&mut self, cave: &'a str) {
+        let mut repeat = false;
+
+        if let Some(limit) = self.policy.limit(cave) {
+            let visits = self.visits.entry(cave).or_insert(0);
+
+            if *visits < limit {
+                *visits += 1;
+            } else if self.policy.is_repeatable(cave)
+                && self.repeated.iter().filter(|r| **r).count() < self.policy.repeats as usize
+            {
+                repeat = true;
+            } else {
+                return;
+            }
+        }
+
+        self.path.push(cave);
+        self.next.push(0);
+        self.repeated.push(repeat);
+    }
+
+    fn pop(&mut self) {
+        let cave = self.path.pop().unwrap();
+        self.next.pop();
+
+        if !self.repeated.pop().unwrap() {
+            if let Some(visits) = self.visits.get_mut(cave) {
+                *visits -= 1;
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for Paths<'a> {
+    type Item = Path<'a>;
+
+    fn next(&mut self) -> Option<Path<'a>> {
+        while let Some(&cave) = self.path.last() {
+            let top = self.path.len() - 1;
+            let neighbors = &self.graph[cave];
+
+            if self.next[top] == neighbors.len() {
+                self.pop();
+                continue;
+            }
+
+            let node = &neighbors[self.next[top]];
+            self.next[top] += 1;
+
+            if node.name == self.policy.end {
+                let mut path = self.path.clone();
+                path.push(&node.name);
+
+                return Some(path);
+            }
+
+            if node.name != self.policy.start {
+                self.push(&node.name);
+            }
+        }
+
+        None
+    }
+}
+
+pub fn shortest<'a>(paths: impl Iterator<Item = Path<'a>>) -> Vec<Path<'a>> {
+    select(paths, |a, b| b.cmp(&a))
+}
+
+pub fn longest<'a>(paths: impl Iterator<Item = Path<'a>>) -> Vec<Path<'a>> {
+    select(paths, |a, b| a.cmp(&b))
+}
+
+// keeps every path whose length compares best
+fn select<'a>(
+    paths: impl Iterator<Item = Path<'a>>,
+    compare: impl Fn(usize, usize) -> std::cmp::Ordering,
+) -> Vec<Path<'a>> {
+    let mut selected: Vec<Path<'a>> = Vec::new();
+
+    for path in paths {
+        match selected.first().map(|s| compare(path.len(), s.len())) {
+            Some(std::cmp::Ordering::Less) => {}
+            Some(std::cmp::Ordering::Equal) => selected.push(path),
+            _ => selected = vec![path],
+        }
+    }
+
+    selected
+}
+
+// small caves are ellipses, big ones boxes and forbidden ones octagons,
+// every highlighted path gets its own color
+pub fn to_dot(graph: &Graph, policy: &VisitPolicy, highlighted: &[Path]) -> String {
+    let mut lines = vec!["graph caves {".to_string()];
+
+    for name in graph.keys() {
+        let shape = match name {
+            _ if *name == policy.start || *name == policy.end => "doublecircle",
+            _ if policy.forbidden.contains(name) => "octagon",
+            _ if is_all_lower(name) => "ellipse",
+            _ => "box",
+        };
+
+        lines.push(format!("    \"{}\" [shape={}];", name, shape));
+    }
+
+    let edges: BTreeSet<(&str, &str)> = graph
+        .iter()
+        .flat_map(|(name, neighbors)| {
+            neighbors.iter().map(move |n| {
+                let (a, b) = (name.as_str(), n.name.as_str());
+                (a.min(b), a.max(b))
+            })
+        })
+        .collect();
+
+    for (a, b) in edges {
+        lines.push(format!("    \"{}\" -- \"{}\";", a, b));
+    }
+
+    for (i, path) in highlighted.iter().enumerate() {
+        for pair in path.windows(2) {
+            lines.push(format!(
+                "    \"{}\" -- \"{}\" [color={}, penwidth=2];",
+                pair[0],
+                pair[1],
+                COLORS[i % COLORS.len()]
+            ));
+        }
+    }
+
+    lines.push("}".to_string());
+
+    lines.join("\n")
+}
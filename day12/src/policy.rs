@@ -0,0 +1,61 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::is_all_lower;
+
+// Small caves may be entered once unless a limit says otherwise, every
+// repeat lets one small cave be entered past its limit. The start cave is
+// never entered again and big caves are only bounded by explicit limits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VisitPolicy {
+    pub start: String,
+    pub end: String,
+    pub repeats: u32,
+    pub limits: BTreeMap<String, u32>,
+    pub forbidden: BTreeSet<String>,
+}
+
+impl Default for VisitPolicy {
+    fn default() -> Self {
+        VisitPolicy {
+            start: "start".to_string(),
+            end: "end".to_string(),
+            repeats: 0,
+            limits: BTreeMap::new(),
+            forbidden: BTreeSet::new(),
+        }
+    }
+}
+
+impl VisitPolicy {
+    pub fn with_repeats(repeats: u32) -> Self {
+        VisitPolicy {
+            repeats,
+            ..VisitPolicy::default()
+        }
+    }
+
+    pub fn limit(&self, cave: &str) -> Option<u32> {
+        if self.forbidden.contains(cave) {
+            Some(0)
+        } else if let Some(&limit) = self.limits.get(cave) {
+            Some(limit)
+        } else if is_all_lower(cave) {
+            Some(1)
+        } else {
+            None
+        }
+    }
+
+    // the end is reached by entering it, so a policy that keeps it
+    // from being entered leaves no path at all
+    pub fn allows_entry(&self, cave: &str) -> bool {
+        self.limit(cave) != Some(0)
+    }
+
+    pub fn is_repeatable(&self, cave: &str) -> bool {
+        is_all_lower(cave)
+            && cave != self.start
+            && cave != self.end
+            && !self.forbidden.contains(cave)
+    }
+}
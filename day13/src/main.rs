@@ -1,74 +1,65 @@
-use std::{collections::BTreeSet, fs};
+mod ocr;
+mod sheet;
+
+use std::{collections::BTreeSet, env, fs};
 
 use lazy_static::lazy_static;
 use regex::{Match, Regex};
+use sheet::Sheet;
 
 fn main() {
     let input = fs::read_to_string("./_data/input.txt").expect("oh noes");
 
+    // [frames path], gif files are animated, anything else gets ascii frames
+    if let Some(path) = env::args().nth(1) {
+        export_frames(input.clone(), &path);
+    }
+
     let output = process_data(input.clone());
-    let adv_output = process_data_adv(input);
+    let adv_output = process_data_adv(input.clone());
+
+    println!("{}\n", ocr::render(&get_folded_dots(input)));
 
     println!("Result is: {}", output);
     println!("Adv result is: {}", adv_output);
 }
 
 fn process_data(input: String) -> String {
-    let (mut dots, folds) = parse(input);
+    let (dots, folds) = parse(input);
 
-    apply_fold(&mut dots, folds.get(0).unwrap());
-
-    dots.sort_unstable();
-    dots.dedup();
-
-    dots.len().to_string()
+    Sheet::new(&dots)
+        .fold(folds.first().unwrap())
+        .dots
+        .len()
+        .to_string()
 }
 
+// falls back to the rendered dots when they are not readable
 fn process_data_adv(input: String) -> String {
-    let (mut dots, folds) = parse(input);
-
-    for fold in folds.iter() {
-        apply_fold(&mut dots, fold);
-    }
+    let dots = get_folded_dots(input);
 
-    let dot_set = BTreeSet::from_iter(dots.iter());
-
-    println!();
-
-    for y in 0u32..6 {
-        let line = (0u32..39)
-            .map(|x| if dot_set.contains(&(x, y)) { '#' } else { ' ' })
-            .collect::<String>();
-
-        println!("{}", line);
-    }
+    ocr::recognize(&dots).unwrap_or_else(|_| ocr::render(&dots))
+}
 
-    println!();
+fn get_folded_dots(input: String) -> BTreeSet<(u32, u32)> {
+    let (dots, folds) = parse(input);
 
-    "printed".to_owned()
+    sheet::fold_all(Sheet::new(&dots), &folds)
+        .pop()
+        .unwrap()
+        .dots
 }
 
-fn apply_fold(dots: &mut Vec<(u32, u32)>, fold: &Fold) {
-    match fold {
-        Fold::X(x) => apply_fold_x(dots, x),
-        Fold::Y(y) => apply_fold_y(dots, y),
-    }
-}
+fn export_frames(input: String, path: &str) {
+    let (dots, folds) = parse(input);
+    let frames = sheet::fold_all(Sheet::new(&dots), &folds);
 
-fn apply_fold_x(dots: &mut Vec<(u32, u32)>, x: &u32) {
-    for dot in dots.iter_mut() {
-        if dot.0 > *x {
-            dot.0 = 2 * x - dot.0
-        }
-    }
-}
+    let content = match path.rsplit('.').next() {
+        Some("gif") => sheet::to_gif(&frames, 1),
+        _ => sheet::to_ascii_frames(&frames).into_bytes(),
+    };
 
-fn apply_fold_y(dots: &mut Vec<(u32, u32)>, y: &u32) {
-    for dot in dots.iter_mut() {
-        if dot.1 > *y {
-            dot.1 = 2 * y - dot.1
-        }
-    }
+    fs::write(path, content).expect("oh noes");
 }
 
 fn parse(input: String) -> (Vec<(u32, u32)>, Vec<Fold>) {
@@ -140,4 +131,93 @@ mod tests {
     fn base_check() {
         assert_eq!("17", process_data(TEST_CASE.to_string()));
     }
+
+    #[test]
+    fn adv_check() {
+        assert_eq!(
+            "#####\n#...#\n#...#\n#...#\n#####",
+            process_data_adv(TEST_CASE.to_string())
+        );
+    }
+
+    #[test]
+    fn ocr_check() {
+        let rendered = "\
+            .##..###....##.###..####.###..#..#.#...
+            #..#.#..#....#.#..#.#....#..#.#..#.#...
+            #....#..#....#.###..###..#..#.#..#.#...
+            #....###.....#.#..#.#....###..#..#.#...
+            #..#.#....#..#.#..#.#....#.#..#..#.#...
+            .##..#.....##..###..####.#..#..##..####";
+
+        let dots: BTreeSet<(u32, u32)> = rendered
+            .lines()
+            .enumerate()
+            .flat_map(|(y, l)| {
+                l.trim()
+                    .chars()
+                    .enumerate()
+                    .filter(|(_, c)| *c == '#')
+                    .map(move |(x, _)| (x as u32 + 3, y as u32 + 2))
+            })
+            .collect();
+
+        assert_eq!(Some(((3, 2), (41, 7))), ocr::get_bounding_box(&dots));
+        assert_eq!(
+            rendered.lines().map(|l| l.trim()).collect::<Vec<_>>(),
+            ocr::render(&dots).lines().collect::<Vec<_>>()
+        );
+        assert_eq!(Ok("CPJBERUL".to_string()), ocr::recognize(&dots));
+
+        let mut smudged = dots.clone();
+        smudged.insert((9, 4));
+
+        assert_eq!(
+            Err(ocr::OcrError::UnknownGlyph(1)),
+            ocr::recognize(&smudged)
+        );
+        smudged.insert((9, 8));
+        assert_eq!(Err(ocr::OcrError::Height(7)), ocr::recognize(&smudged));
+    }
+
+    #[test]
+    fn asymmetric_fold_check() {
+        let sheet = Sheet::new(&[(0, 0), (1, 1), (6, 0), (5, 2)]);
+
+        let folded = sheet.fold(&Fold::X(2));
+        assert_eq!((4, 3), (folded.width, folded.height));
+        assert_eq!("#.#.\n...#\n.#..", folded.to_ascii());
+        assert_eq!("###.", folded.fold(&Fold::Y(1)).to_ascii());
+
+        let folded = sheet.fold(&Fold::Y(0));
+        assert_eq!((7, 2), (folded.width, folded.height));
+        assert_eq!(BTreeSet::from([(1, 1), (5, 0)]), folded.dots);
+        assert_eq!(sheet, sheet.fold(&Fold::Y(10)));
+    }
+
+    #[test]
+    fn frames_check() {
+        let (dots, folds) = parse(TEST_CASE.to_string());
+        let frames = sheet::fold_all(Sheet::new(&dots), &folds);
+
+        assert_eq!(
+            vec![(11, 15), (11, 7), (5, 7)],
+            frames
+                .iter()
+                .map(|f| (f.width, f.height))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![18, 17, 16],
+            frames.iter().map(|f| f.dots.len()).collect::<Vec<_>>()
+        );
+
+        let ascii = sheet::to_ascii_frames(&frames);
+        assert!(ascii.starts_with("frame 0 (11x15)\n...#..#..#.\n"));
+        assert!(ascii.ends_with("frame 2 (5x7)\n#####\n#...#\n#...#\n#...#\n#####\n.....\n....."));
+
+        let gif = sheet::to_gif(&frames, 2);
+        assert!(gif.starts_with(b"GIF89a\x16\x00\x1e\x00"));
+        assert_eq!(Some(&0x3B), gif.last());
+    }
 }
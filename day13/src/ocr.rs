@@ -0,0 +1,108 @@
+use std::{collections::BTreeSet, fmt};
+
+const GLYPH_WIDTH: usize = 4;
+const GLYPH_HEIGHT: usize = 6;
+
+// block letters as drawn by the thermal camera, glyphs are
+// separated by one empty column
+const LETTERS: [(char, &str); 16] = [
+    ('A', ".##.#..##..######..##..#"),
+    ('B', "###.#..####.#..##..####."),
+    ('C', ".##.#..##...#...#..#.##."),
+    ('E', "#####...###.#...#...####"),
+    ('F', "#####...###.#...#...#..."),
+    ('G', ".##.#..##...#.###..#.###"),
+    ('H', "#..##..######..##..##..#"),
+    ('J', "..##...#...#...##..#.##."),
+    ('K', "#..##.#.##..#.#.#.#.#..#"),
+    ('L', "#...#...#...#...#...####"),
+    ('O', ".##.#..##..##..##..#.##."),
+    ('P', "###.#..##..####.#...#..."),
+    ('R', "###.#..##..####.#.#.#..#"),
+    ('S', ".####...#....##....####."),
+    ('U', "#..##..##..##..##..#.##."),
+    ('Z', "####...#..#..#..#...####"),
+];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum OcrError {
+    Empty,
+    Height(usize),
+    UnknownGlyph(usize),
+}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OcrError::Empty => write!(f, "No dots to read"),
+            OcrError::Height(height) => {
+                write!(
+                    f,
+                    "Letters are {} dots high, expected {}",
+                    height, GLYPH_HEIGHT
+                )
+            }
+            OcrError::UnknownGlyph(index) => write!(f, "Unknown letter at position {}", index),
+        }
+    }
+}
+
+pub fn get_bounding_box(dots: &BTreeSet<(u32, u32)>) -> Option<((u32, u32), (u32, u32))> {
+    let min_x = dots.iter().map(|d| d.0).min()?;
+    let max_x = dots.iter().map(|d| d.0).max()?;
+    let min_y = dots.iter().map(|d| d.1).min()?;
+    let max_y = dots.iter().map(|d| d.1).max()?;
+
+    Some(((min_x, min_y), (max_x, max_y)))
+}
+
+pub fn render(dots: &BTreeSet<(u32, u32)>) -> String {
+    let ((min_x, min_y), (max_x, max_y)) = match get_bounding_box(dots) {
+        Some(bounds) => bounds,
+        None => return String::new(),
+    };
+
+    (min_y..=max_y)
+        .map(|y| {
+            (min_x..=max_x)
+                .map(|x| if dots.contains(&(x, y)) { '#' } else { '.' })
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+pub fn recognize(dots: &BTreeSet<(u32, u32)>) -> Result<String, OcrError> {
+    let rendered = render(dots);
+    let rows: Vec<&[u8]> = rendered.lines().map(|l| l.as_bytes()).collect();
+
+    if rows.is_empty() {
+        return Err(OcrError::Empty);
+    }
+
+    if rows.len() != GLYPH_HEIGHT {
+        return Err(OcrError::Height(rows.len()));
+    }
+
+    // the last glyph may end in empty columns outside of the bounding box
+    let width = rows[0].len();
+    let count = (width + 1).div_ceil(GLYPH_WIDTH + 1);
+
+    (0..count)
+        .map(|i| {
+            let start = i * (GLYPH_WIDTH + 1);
+            let glyph: String = rows
+                .iter()
+                .flat_map(|row| {
+                    (start..start + GLYPH_WIDTH).map(|x| *row.get(x).unwrap_or(&b'.') as char)
+                })
+                .collect();
+
+            LETTERS
+                .iter()
+                .find(|(_, pattern)| *pattern == glyph)
+                .map(|(letter, _)| *letter)
+                .ok_or(OcrError::UnknownGlyph(i))
+        })
+        .collect()
+}
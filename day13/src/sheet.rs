@@ -0,0 +1,196 @@
+use std::{cmp::Ordering, collections::BTreeSet};
+
+use crate::Fold;
+
+const FRAME_DELAY: u16 = 80;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sheet {
+    pub width: u32,
+    pub height: u32,
+    pub dots: BTreeSet<(u32, u32)>,
+}
+
+impl Sheet {
+    // the sheet ends at its outermost dots
+    pub fn new(dots: &[(u32, u32)]) -> Self {
+        Sheet {
+            width: dots.iter().map(|d| d.0 + 1).max().unwrap_or(0),
+            height: dots.iter().map(|d| d.1 + 1).max().unwrap_or(0),
+            dots: dots.iter().copied().collect(),
+        }
+    }
+
+    // When the folded part is the larger one it sticks out past the
+    // remaining edge, the sheet is then shifted to start at zero again.
+    // Dots on the fold line itself are lost in the crease.
+    pub fn fold(&self, fold: &Fold) -> Sheet {
+        match fold {
+            Fold::X(x) => {
+                let (width, fold_dot) = get_folded(self.width, *x);
+
+                Sheet {
+                    width,
+                    height: self.height,
+                    dots: self
+                        .dots
+                        .iter()
+                        .filter_map(|&(dx, dy)| Some((fold_dot(dx)?, dy)))
+                        .collect(),
+                }
+            }
+            Fold::Y(y) => {
+                let (height, fold_dot) = get_folded(self.height, *y);
+
+                Sheet {
+                    width: self.width,
+                    height,
+                    dots: self
+                        .dots
+                        .iter()
+                        .filter_map(|&(dx, dy)| Some((dx, fold_dot(dy)?)))
+                        .collect(),
+                }
+            }
+        }
+    }
+
+    pub fn to_ascii(&self) -> String {
+        (0..self.height)
+            .map(|y| {
+                (0..self.width)
+                    .map(|x| {
+                        if self.dots.contains(&(x, y)) {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+// new size of the sheet along the fold and the new coordinate of a dot
+fn get_folded(size: u32, line: u32) -> (u32, impl Fn(u32) -> Option<u32>) {
+    let kept = line.min(size);
+    let folded = size.saturating_sub(line + 1);
+    let offset = folded.saturating_sub(kept);
+
+    let fold_dot = move |d: u32| match d.cmp(&line) {
+        Ordering::Greater => Some(2 * line + offset - d),
+        Ordering::Less => Some(d + offset),
+        Ordering::Equal => None,
+    };
+
+    (kept.max(folded), fold_dot)
+}
+
+// the unfolded sheet followed by the sheet after each fold
+pub fn fold_all(sheet: Sheet, folds: &[Fold]) -> Vec<Sheet> {
+    let mut frames = vec![sheet];
+
+    for fold in folds {
+        let next = frames.last().unwrap().fold(fold);
+        frames.push(next);
+    }
+
+    frames
+}
+
+pub fn to_ascii_frames(frames: &[Sheet]) -> String {
+    frames
+        .iter()
+        .enumerate()
+        .map(|(i, f)| format!("frame {} ({}x{})\n{}", i, f.width, f.height, f.to_ascii()))
+        .collect::<Vec<String>>()
+        .join("\n\n")
+}
+
+// Looping GIF with every frame drawn on a canvas as big as the largest
+// sheet, each dot is scale pixels wide.
+pub fn to_gif(frames: &[Sheet], scale: u32) -> Vec<u8> {
+    let width = frames.iter().map(|f| f.width).max().unwrap_or(0) * scale;
+    let height = frames.iter().map(|f| f.height).max().unwrap_or(0) * scale;
+
+    assert!(
+        width <= u16::MAX as u32 && height <= u16::MAX as u32,
+        "Sheet too large"
+    );
+
+    let mut output = b"GIF89a".to_vec();
+    output.extend((width as u16).to_le_bytes());
+    output.extend((height as u16).to_le_bytes());
+    // global table of four colors, white paper and black dots
+    output.extend([0b1000_0001, 0, 0]);
+    output.extend([255, 255, 255, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    // loop forever
+    output.extend([0x21, 0xFF, 0x0B]);
+    output.extend(b"NETSCAPE2.0");
+    output.extend([0x03, 0x01, 0x00, 0x00, 0x00]);
+
+    for frame in frames {
+        output.extend([0x21, 0xF9, 0x04, 0x00]);
+        output.extend(FRAME_DELAY.to_le_bytes());
+        output.extend([0x00, 0x00]);
+
+        output.push(0x2C);
+        output.extend([0, 0, 0, 0]);
+        output.extend((width as u16).to_le_bytes());
+        output.extend((height as u16).to_le_bytes());
+        output.push(0);
+
+        let pixels = (0..height).flat_map(|y| {
+            (0..width).map(move |x| frame.dots.contains(&(x / scale, y / scale)) as u8)
+        });
+
+        output.push(2);
+
+        for block in encode_lzw(pixels).chunks(255) {
+            output.push(block.len() as u8);
+            output.extend(block);
+        }
+
+        output.push(0);
+    }
+
+    output.push(0x3B);
+
+    output
+}
+
+// Every pixel is written as a literal 3 bit code, a clear code after every
+// second pixel keeps the decoder table from growing past that width.
+fn encode_lzw(pixels: impl Iterator<Item = u8>) -> Vec<u8> {
+    const CLEAR: u32 = 4;
+    const END: u32 = 5;
+
+    let mut codes = Vec::new();
+
+    for (i, pixel) in pixels.enumerate() {
+        if i % 2 == 0 {
+            codes.push(CLEAR);
+        }
+
+        codes.push(pixel as u32);
+    }
+
+    codes.push(END);
+
+    let mut bytes = vec![0u8; (codes.len() * 3).div_ceil(8)];
+
+    for (i, code) in codes.into_iter().enumerate() {
+        let bit = i * 3;
+        let value = code << (bit % 8);
+
+        bytes[bit / 8] |= value as u8;
+
+        if value > 0xFF {
+            bytes[bit / 8 + 1] |= (value >> 8) as u8;
+        }
+    }
+
+    bytes
+}
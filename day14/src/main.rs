@@ -1,10 +1,48 @@
-use std::{collections::BTreeMap, fs};
+mod report;
+mod transition;
+
+use std::{collections::BTreeMap, env, fs, num::NonZeroU64};
 
 use lazy_static::lazy_static;
 use regex::{Match, Regex};
+use transition::{Arithmetic, Polymer};
 
 fn main() {
     let input = fs::read_to_string("./_data/input.txt").expect("oh noes");
+    let args: Vec<String> = env::args().collect();
+
+    // counts <steps> [modulus] or report <steps> <path>
+    if args.get(1).map(|a| &a[..]) == Some("report") {
+        let steps = args[2].parse::<u64>().unwrap();
+        let (template, rules) = parse(input.clone());
+
+        match report::get_report(&template, &rules, steps, MAX_POLYMER_LENGTH) {
+            Some(report) => fs::write(&args[3], report::to_csv(&report)).expect("oh noes"),
+            None => println!("Element counts after {} steps overflow", steps),
+        }
+    } else if args.get(1).map(|a| &a[..]) == Some("counts") {
+        let steps = args[2].parse::<u64>().unwrap();
+        let arithmetic = args
+            .get(3)
+            .map(|m| {
+                Arithmetic::Modulo(
+                    m.parse::<NonZeroU64>()
+                        .expect("Modulus must be a positive number"),
+                )
+            })
+            .unwrap_or(Arithmetic::Checked);
+
+        let (template, rules) = parse(input.clone());
+
+        match Polymer::new(&template, &rules).element_counts(steps, arithmetic) {
+            Some(counts) => {
+                for (element, count) in counts.iter() {
+                    println!("{}: {}", element, count);
+                }
+            }
+            None => println!("Element counts after {} steps overflow", steps),
+        }
+    }
 
     let output = process_data(input.clone());
     let adv_output = process_data_adv(input);
@@ -14,55 +52,28 @@ fn main() {
 }
 
 fn process_data(input: String) -> String {
-    let (mut template, rules, mut counts) = parse(input);
-
-    for _ in 0..10 {
-        apply_rules(&rules, &mut template, &mut counts)
-    }
-
-    (counts.values().max().unwrap() - counts.values().min().unwrap()).to_string()
+    get_spread(input, 10).to_string()
 }
 
 fn process_data_adv(input: String) -> String {
-    let (mut template, rules, mut counts) = parse(input);
-
-    for _ in 0..40 {
-        apply_rules(&rules, &mut template, &mut counts)
-    }
-
-    (counts.values().max().unwrap() - counts.values().min().unwrap()).to_string()
+    get_spread(input, 40).to_string()
 }
 
-type RuleMap = BTreeMap<(char, char), char>;
-type PolymerPairs = BTreeMap<(char, char), u64>;
-type RunningCount = BTreeMap<char, u64>;
+// difference between the most and the least common element
+fn get_spread(input: String, steps: u64) -> u128 {
+    let (template, rules) = parse(input);
+    let counts = Polymer::new(&template, &rules)
+        .element_counts(steps, Arithmetic::Checked)
+        .expect("Element count overflow");
 
-fn apply_rules(rules: &RuleMap, pairs: &mut PolymerPairs, counts: &mut RunningCount) {
-    let mut to_remove = Vec::new();
-    let mut to_add = Vec::new();
-
-    for (template, &replacement) in rules.iter() {
-        if let Some(&current) = pairs.get(template) {
-            to_remove.push(template);
-            to_add.push(((template.0, replacement), current));
-            to_add.push(((replacement, template.1), current));
-
-            let count = counts.entry(replacement).or_insert(0);
-            *count += current;
-        }
-    }
+    counts.values().max().unwrap() - counts.values().min().unwrap()
+}
 
-    for rem in to_remove.iter() {
-        pairs.remove(rem);
-    }
+const MAX_POLYMER_LENGTH: usize = 1 << 16;
 
-    for (key, value) in to_add.iter() {
-        let pair = pairs.entry(*key).or_insert(0);
-        *pair += value;
-    }
-}
+type RuleMap = BTreeMap<(char, char), char>;
 
-fn parse(input: String) -> (PolymerPairs, RuleMap, RunningCount) {
+fn parse(input: String) -> (Vec<char>, RuleMap) {
     lazy_static! {
         static ref RULE_RE: Regex = Regex::new("^([A-Z])([A-Z]) -> ([A-Z])$").unwrap();
     }
@@ -71,21 +82,6 @@ fn parse(input: String) -> (PolymerPairs, RuleMap, RunningCount) {
 
     let template: Vec<char> = lines.first().map(|l| l.chars().collect()).unwrap();
 
-    let mut running_count = BTreeMap::new();
-    for &c in template.iter() {
-        let count = running_count.entry(c).or_insert(0);
-        *count += 1;
-    }
-
-    let mut template_pairs = BTreeMap::new();
-    template
-        .iter()
-        .zip(template.iter().skip(1))
-        .for_each(|(&c1, &c2)| {
-            let entry = template_pairs.entry((c1, c2)).or_insert(0);
-            *entry += 1;
-        });
-
     let rules = BTreeMap::from_iter(
         lines
             .iter()
@@ -102,7 +98,7 @@ fn parse(input: String) -> (PolymerPairs, RuleMap, RunningCount) {
             }),
     );
 
-    (template_pairs, rules, running_count)
+    (template, rules)
 }
 
 fn parse_as_char(cap: Option<Match>) -> Option<char> {
@@ -142,4 +138,86 @@ mod tests {
     fn adv_check() {
         assert_eq!("2188189693529", process_data_adv(TEST_CASE.to_string()));
     }
+
+    #[test]
+    fn fast_forward_check() {
+        let (template, rules) = parse(TEST_CASE.to_string());
+        let polymer = Polymer::new(&template, &rules);
+
+        assert_eq!(
+            Some(BTreeMap::from([('B', 2), ('C', 2), ('H', 1), ('N', 2)])),
+            polymer.element_counts(1, Arithmetic::Checked)
+        );
+        assert_eq!(
+            Some(3073),
+            polymer
+                .element_counts(10, Arithmetic::Checked)
+                .map(|c| c.values().sum::<u128>())
+        );
+        assert_eq!(None, polymer.element_counts(200, Arithmetic::Checked));
+
+        let modulus = 1_000_000_007;
+        let modulo = Arithmetic::Modulo(NonZeroU64::new(modulus).unwrap());
+        let exact = polymer.element_counts(40, Arithmetic::Checked).unwrap();
+        let modular = polymer.element_counts(40, modulo).unwrap();
+
+        assert_eq!(
+            exact
+                .values()
+                .map(|c| c % modulus as u128)
+                .collect::<Vec<_>>(),
+            modular.values().copied().collect::<Vec<_>>()
+        );
+
+        // every step doubles the pairs, so there are 3 * 2^n + 1 elements
+        let mut length = 3u128;
+        let (mut base, mut exponent) = (2u128, 1_000_000_000u64);
+
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                length = length * base % modulus as u128;
+            }
+
+            base = base * base % modulus as u128;
+            exponent >>= 1;
+        }
+
+        let huge = polymer.element_counts(1_000_000_000, modulo).unwrap();
+
+        assert_eq!(
+            (length + 1) % modulus as u128,
+            huge.values().sum::<u128>() % modulus as u128
+        );
+    }
+
+    #[test]
+    fn report_check() {
+        let (template, rules) = parse(TEST_CASE.to_string());
+        let report = report::get_report(&template, &rules, 10, 60).unwrap();
+
+        assert_eq!(11, report.len());
+        assert_eq!(Some("NCNBCHB".to_string()), report[1].polymer);
+        assert_eq!(
+            Some("NBBNBNBBCCNBCNCCNBBNBBNBBBNBBNBBCBHCBHHNHCBBCBHCB".to_string()),
+            report[4].polymer
+        );
+        assert_eq!(None, report[5].polymer);
+        assert!(report[..5].iter().all(|s| s.verified == Some(true)));
+        assert_eq!(None, report[5].verified);
+
+        assert_eq!(Some(&1.0), report[1].growth.get(&'N'));
+        assert_eq!(None, report[1].growth.get(&'H'));
+        assert_eq!(Some(&3.0), report[2].growth.get(&'B'));
+        assert_eq!(
+            Some((&1749, &161)),
+            report[10].counts.get(&'B').zip(report[10].counts.get(&'H'))
+        );
+
+        let csv = report::to_csv(&report);
+        assert!(csv.starts_with(
+            "step,B,C,H,N,B growth,C growth,H growth,N growth,verified,polymer\n0,1,1,0,2,,,,,true,NNCB\n"
+        ));
+        assert_eq!(12, csv.lines().count());
+        assert_eq!(None, report::get_report(&template, &rules, 200, 60));
+    }
 }
@@ -0,0 +1,142 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    transition::{Arithmetic, Polymer},
+    RuleMap,
+};
+
+#[derive(Debug, PartialEq)]
+pub struct StepStats {
+    pub step: u64,
+    pub counts: BTreeMap<char, u128>,
+    // ratio to the count of the previous step, elements that were
+    // missing before have none
+    pub growth: BTreeMap<char, f64>,
+    pub polymer: Option<String>,
+    // whether the polymer holds exactly the counts of the pair model
+    pub verified: Option<bool>,
+}
+
+// polymers are only materialized while they have at most max_length elements
+pub fn get_report(
+    template: &[char],
+    rules: &RuleMap,
+    steps: u64,
+    max_length: usize,
+) -> Option<Vec<StepStats>> {
+    let series = Polymer::new(template, rules).series(steps, Arithmetic::Checked)?;
+
+    let mut polymer = Some(template.to_vec());
+    let mut report: Vec<StepStats> = Vec::with_capacity(series.len());
+
+    for (step, counts) in series.into_iter().enumerate() {
+        if step > 0 {
+            polymer = polymer.and_then(|p| apply_rules(&p, rules, max_length));
+        }
+
+        let growth = match report.last() {
+            Some(previous) => get_growth(&previous.counts, &counts),
+            None => BTreeMap::new(),
+        };
+
+        let verified = polymer.as_ref().map(|p| get_counts(p) == counts);
+
+        report.push(StepStats {
+            step: step as u64,
+            counts,
+            growth,
+            polymer: polymer.as_ref().map(|p| p.iter().collect()),
+            verified,
+        });
+    }
+
+    Some(report)
+}
+
+pub fn to_csv(report: &[StepStats]) -> String {
+    let elements: BTreeSet<char> = report
+        .iter()
+        .flat_map(|s| s.counts.keys())
+        .copied()
+        .collect();
+
+    let header: Vec<String> = ["step".to_string()]
+        .into_iter()
+        .chain(elements.iter().map(|e| e.to_string()))
+        .chain(elements.iter().map(|e| format!("{} growth", e)))
+        .chain(["verified".to_string(), "polymer".to_string()])
+        .collect();
+
+    let rows = report.iter().map(|s| {
+        let counts = elements
+            .iter()
+            .map(|e| s.counts.get(e).copied().unwrap_or(0).to_string());
+        let growth = elements.iter().map(|e| {
+            s.growth
+                .get(e)
+                .map(|g| format!("{:.4}", g))
+                .unwrap_or_default()
+        });
+        let verified = s.verified.map(|v| v.to_string()).unwrap_or_default();
+        let polymer = s.polymer.clone().unwrap_or_default();
+
+        [s.step.to_string()]
+            .into_iter()
+            .chain(counts)
+            .chain(growth)
+            .chain([verified, polymer])
+            .collect::<Vec<String>>()
+            .join(",")
+    });
+
+    [header.join(",")]
+        .into_iter()
+        .chain(rows)
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn apply_rules(polymer: &[char], rules: &RuleMap, max_length: usize) -> Option<Vec<char>> {
+    let mut output = Vec::with_capacity(polymer.len() * 2);
+
+    for (i, &c) in polymer.iter().enumerate() {
+        output.push(c);
+
+        if let Some(&next) = polymer.get(i + 1) {
+            if let Some(&inserted) = rules.get(&(c, next)) {
+                output.push(inserted);
+            }
+        }
+
+        if output.len() > max_length {
+            return None;
+        }
+    }
+
+    Some(output)
+}
+
+fn get_counts(polymer: &[char]) -> BTreeMap<char, u128> {
+    let mut counts = BTreeMap::new();
+
+    for c in polymer {
+        *counts.entry(*c).or_insert(0) += 1;
+    }
+
+    counts
+}
+
+fn get_growth(
+    previous: &BTreeMap<char, u128>,
+    counts: &BTreeMap<char, u128>,
+) -> BTreeMap<char, f64> {
+    counts
+        .iter()
+        .filter_map(|(e, c)| {
+            previous
+                .get(e)
+                .filter(|p| **p > 0)
+                .map(|p| (*e, *c as f64 / *p as f64))
+        })
+        .collect()
+}
@@ -0,0 +1,184 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    num::NonZeroU64,
+};
+
+use crate::RuleMap;
+
+// counts either have to fit exactly or are only kept modulo a number
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arithmetic {
+    Checked,
+    Modulo(NonZeroU64),
+}
+
+impl Arithmetic {
+    // a * b + c
+    fn mul_add(&self, a: u128, b: u128, c: u128) -> Option<u128> {
+        match self {
+            Arithmetic::Checked => a.checked_mul(b)?.checked_add(c),
+            Arithmetic::Modulo(m) => {
+                let m = m.get() as u128;
+
+                Some(((a % m) * (b % m) % m + c % m) % m)
+            }
+        }
+    }
+}
+
+// Every pair turns into the two pairs around its inserted element, or stays
+// itself without a rule, so a step only moves counts along these successors.
+pub struct Polymer {
+    pairs: Vec<(char, char)>,
+    initial: Vec<u128>,
+    successors: Vec<Vec<usize>>,
+    last: Option<char>,
+}
+
+impl Polymer {
+    pub fn new(template: &[char], rules: &RuleMap) -> Self {
+        let template_pairs: Vec<(char, char)> = template.windows(2).map(|w| (w[0], w[1])).collect();
+
+        let known: BTreeSet<(char, char)> = template_pairs
+            .iter()
+            .copied()
+            .chain(
+                rules
+                    .iter()
+                    .flat_map(|(&(a, b), &c)| [(a, b), (a, c), (c, b)]),
+            )
+            .collect();
+        let pairs: Vec<(char, char)> = known.into_iter().collect();
+        let id = |pair: (char, char)| pairs.binary_search(&pair).unwrap();
+
+        let mut initial = vec![0; pairs.len()];
+        for &pair in template_pairs.iter() {
+            initial[id(pair)] += 1;
+        }
+
+        let successors = pairs
+            .iter()
+            .enumerate()
+            .map(|(i, &(a, b))| match rules.get(&(a, b)) {
+                Some(&c) => vec![id((a, c)), id((c, b))],
+                None => vec![i],
+            })
+            .collect();
+
+        Polymer {
+            pairs,
+            initial,
+            successors,
+            last: template.last().copied(),
+        }
+    }
+
+    // Steps are fast-forwarded by doubling: the pairs a single pair grows
+    // into over 2^(k+1) steps are what its pairs after 2^k steps grow into
+    // over another 2^k. Growing pairs that are not in the template can
+    // overflow a checked count even when the counts asked for would not.
+    pub fn pair_counts(&self, steps: u64, arithmetic: Arithmetic) -> Option<Vec<u128>> {
+        let mut expansions: Vec<Vec<u128>> = (0..self.pairs.len())
+            .map(|i| self.step(&self.unit(i), arithmetic))
+            .collect::<Option<_>>()?;
+        let mut counts = self.initial.clone();
+        let mut rest = steps;
+
+        while rest > 0 {
+            if rest & 1 == 1 {
+                counts = expand(&counts, &expansions, arithmetic)?;
+            }
+
+            rest >>= 1;
+
+            if rest > 0 {
+                expansions = expansions
+                    .iter()
+                    .map(|e| expand(e, &expansions, arithmetic))
+                    .collect::<Option<_>>()?;
+            }
+        }
+
+        Some(counts)
+    }
+
+    pub fn series(&self, steps: u64, arithmetic: Arithmetic) -> Option<Vec<BTreeMap<char, u128>>> {
+        let mut counts = self.initial.clone();
+        let mut output = vec![self.get_element_counts(&counts, arithmetic)?];
+
+        for _ in 0..steps {
+            counts = self.step(&counts, arithmetic)?;
+            output.push(self.get_element_counts(&counts, arithmetic)?);
+        }
+
+        Some(output)
+    }
+
+    // every element is counted as the first of a pair, except for the
+    // last one of the template which never moves
+    pub fn element_counts(
+        &self,
+        steps: u64,
+        arithmetic: Arithmetic,
+    ) -> Option<BTreeMap<char, u128>> {
+        self.get_element_counts(&self.pair_counts(steps, arithmetic)?, arithmetic)
+    }
+
+    fn step(&self, counts: &[u128], arithmetic: Arithmetic) -> Option<Vec<u128>> {
+        let mut next = vec![0; counts.len()];
+
+        for (successors, &count) in self.successors.iter().zip(counts) {
+            for &s in successors {
+                next[s] = arithmetic.mul_add(count, 1, next[s])?;
+            }
+        }
+
+        Some(next)
+    }
+
+    fn unit(&self, index: usize) -> Vec<u128> {
+        let mut counts = vec![0; self.pairs.len()];
+        counts[index] = 1;
+
+        counts
+    }
+
+    fn get_element_counts(
+        &self,
+        pair_counts: &[u128],
+        arithmetic: Arithmetic,
+    ) -> Option<BTreeMap<char, u128>> {
+        let mut counts = BTreeMap::new();
+        let firsts = self
+            .pairs
+            .iter()
+            .map(|p| p.0)
+            .zip(pair_counts.iter().copied());
+
+        for (element, count) in firsts.chain(self.last.map(|l| (l, 1))) {
+            let entry = counts.entry(element).or_insert(0);
+            *entry = arithmetic.mul_add(count, 1, *entry)?;
+        }
+
+        counts.retain(|_, c| *c > 0 || matches!(arithmetic, Arithmetic::Modulo(_)));
+
+        Some(counts)
+    }
+}
+
+// the pairs grown from every pair in counts, weighted by how often it occurs
+fn expand(counts: &[u128], expansions: &[Vec<u128>], arithmetic: Arithmetic) -> Option<Vec<u128>> {
+    let mut output = vec![0; counts.len()];
+
+    for (&count, expansion) in counts.iter().zip(expansions) {
+        if count == 0 {
+            continue;
+        }
+
+        for (o, &e) in output.iter_mut().zip(expansion) {
+            *o = arithmetic.mul_add(count, e, *o)?;
+        }
+    }
+
+    Some(output)
+}
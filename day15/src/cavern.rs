@@ -0,0 +1,152 @@
+use std::str::FromStr;
+
+// what happens to risks raised past the limit by tiling
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapRule {
+    Cycle(u32),
+    Saturate(u32),
+    Unbounded,
+}
+
+impl WrapRule {
+    fn apply(&self, risk: u32) -> u32 {
+        match *self {
+            WrapRule::Cycle(limit) if risk > limit => (risk - 1) % limit + 1,
+            WrapRule::Saturate(limit) => risk.min(limit),
+            _ => risk,
+        }
+    }
+}
+
+impl FromStr for WrapRule {
+    type Err = String;
+
+    // cycle:N, saturate:N or none, risks never go below one so
+    // neither rule makes sense with a limit of zero
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let limit = |l: &str| match l.parse::<u32>() {
+            Ok(0) => Err(format!("Wrap limit must be positive: {}", s)),
+            Ok(limit) => Ok(limit),
+            Err(e) => Err(e.to_string()),
+        };
+
+        match s.split_once(':') {
+            Some(("cycle", l)) => Ok(WrapRule::Cycle(limit(l)?)),
+            Some(("saturate", l)) => Ok(WrapRule::Saturate(limit(l)?)),
+            None if s == "none" => Ok(WrapRule::Unbounded),
+            _ => Err(format!("Unknown wrap rule: {}", s)),
+        }
+    }
+}
+
+// Every tile repeats the scanned cavern with risks raised by its
+// distance in tiles from the top left one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tiling {
+    pub horizontal: usize,
+    pub vertical: usize,
+    pub wrap: WrapRule,
+}
+
+impl Default for Tiling {
+    fn default() -> Self {
+        Tiling {
+            horizontal: 1,
+            vertical: 1,
+            wrap: WrapRule::Cycle(9),
+        }
+    }
+}
+
+// risks of tiled cells are computed on access, only the scanned part is stored
+pub struct Cavern {
+    width: usize,
+    height: usize,
+    risks: Vec<u32>,
+    tiling: Tiling,
+    diagonal: bool,
+}
+
+impl Cavern {
+    pub fn new(width: usize, risks: Vec<u32>, tiling: Tiling, diagonal: bool) -> Self {
+        assert!(
+            width > 0 && risks.len().is_multiple_of(width),
+            "Cavern is not rectangular"
+        );
+
+        Cavern {
+            width,
+            height: risks.len() / width,
+            risks,
+            tiling,
+            diagonal,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width * self.tiling.horizontal
+    }
+
+    pub fn height(&self) -> usize {
+        self.height * self.tiling.vertical
+    }
+
+    pub fn area(&self) -> usize {
+        self.width() * self.height()
+    }
+
+    pub fn is_diagonal(&self) -> bool {
+        self.diagonal
+    }
+
+    pub fn risk(&self, position: usize) -> u32 {
+        let (x, y) = (position % self.width(), position / self.width());
+        let base = self.risks[(y % self.height) * self.width + x % self.width];
+        let raise = (x / self.width + y / self.height) as u32;
+
+        self.tiling.wrap.apply(base + raise)
+    }
+
+    pub fn min_risk(&self) -> u32 {
+        let max_raise = (self.tiling.horizontal + self.tiling.vertical).saturating_sub(2) as u32;
+
+        (0..=max_raise)
+            .flat_map(|raise| {
+                self.risks
+                    .iter()
+                    .map(move |r| self.tiling.wrap.apply(r + raise))
+            })
+            .min()
+            .unwrap_or(0)
+    }
+
+    pub fn neighbors(&self, position: usize) -> Vec<usize> {
+        let offsets: &[(isize, isize)] = if self.diagonal {
+            &[
+                (0, -1),
+                (-1, 0),
+                (1, 0),
+                (0, 1),
+                (-1, -1),
+                (1, -1),
+                (-1, 1),
+                (1, 1),
+            ]
+        } else {
+            &[(0, -1), (-1, 0), (1, 0), (0, 1)]
+        };
+
+        let (width, height) = (self.width() as isize, self.height() as isize);
+        let (x, y) = (
+            (position % self.width()) as isize,
+            (position / self.width()) as isize,
+        );
+
+        offsets
+            .iter()
+            .map(|(dx, dy)| (x + dx, y + dy))
+            .filter(|(nx, ny)| *nx >= 0 && *ny >= 0 && *nx < width && *ny < height)
+            .map(|(nx, ny)| (ny * width + nx) as usize)
+            .collect()
+    }
+}
@@ -1,13 +1,38 @@
-mod dedup_queue;
+mod cavern;
+mod search;
 
-use std::{collections::BTreeMap, fs};
+use std::{env, fmt, fs};
 
-use dedup_queue::DedupQueue;
+use cavern::{Cavern, Tiling};
+use search::{find_route, Strategy};
 
-// TODO: use 'BinaryHeap' instead of custom collection?
-// TODO: use Dijkstra algorithm instead of made up solution?
 fn main() {
     let input = fs::read_to_string("./_data/input.txt").expect("oh noes");
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    // [dijkstra|astar] [render path] [--tiles=WxH] [--wrap=RULE] [--diagonal]
+    let (flags, positional): (Vec<&String>, Vec<&String>) =
+        args.iter().partition(|a| a.starts_with("--"));
+
+    if let Some(strategy) = positional.first() {
+        let strategy = strategy.parse::<Strategy>().unwrap();
+        let (tiling, diagonal) = get_options(&flags);
+
+        match parse(input.clone(), tiling, diagonal) {
+            Ok(cavern) => match find_route(&cavern, strategy) {
+                Some(route) => {
+                    println!("Custom result is: {}", route.risk);
+
+                    if let Some(path) = positional.get(1) {
+                        let rendered = search::render_route(&cavern, &route);
+                        fs::write(path, rendered).expect("oh noes");
+                    }
+                }
+                None => println!("No route through the cavern"),
+            },
+            Err(e) => println!("{}", e),
+        }
+    }
 
     let output = process_data(input.clone());
     let adv_output = process_data_adv(input);
@@ -17,129 +42,125 @@ fn main() {
 }
 
 fn process_data(input: String) -> String {
-    let (cavern, sizing) = parse(input);
+    let cavern = parse(input, Tiling::default(), false).unwrap_or_else(|e| panic!("{}", e));
 
-    find_shortest_path_len(cavern, sizing).to_string()
+    find_shortest_path_len(&cavern).to_string()
 }
 
 fn process_data_adv(input: String) -> String {
-    let (base_cavern, base_sizing) = parse(input);
-    let (cavern, sizing) = inflate_5(base_cavern, base_sizing);
+    let tiling = Tiling {
+        horizontal: 5,
+        vertical: 5,
+        ..Tiling::default()
+    };
+    let cavern = parse(input, tiling, false).unwrap_or_else(|e| panic!("{}", e));
 
-    find_shortest_path_len(cavern, sizing).to_string()
+    find_shortest_path_len(&cavern).to_string()
 }
 
-fn find_shortest_path_len(cavern: Vec<u32>, sizing: CavernSize) -> u32 {
-    let mut shortests = BTreeMap::from([(0usize, 0u32)]);
-
-    let mut queue = DedupQueue::new();
-    queue.push_front(0usize);
-
-    while let Some(next) = queue.pop() {
-        let path = shortests.get(&next).copied().unwrap();
-        let neighs = get_neighbor_positions(next, &sizing);
+fn find_shortest_path_len(cavern: &Cavern) -> u32 {
+    find_route(cavern, Strategy::Dijkstra).unwrap().risk
+}
 
-        for &neigh in neighs.iter() {
-            let neigh_path = path + cavern.get(neigh).unwrap();
+fn get_options(flags: &[&String]) -> (Tiling, bool) {
+    let mut tiling = Tiling::default();
+    let mut diagonal = false;
 
-            if let Some(existing) = shortests.get_mut(&neigh) {
-                if *existing > neigh_path {
-                    *existing = neigh_path;
-                    queue.push_front(neigh);
-                }
-            } else {
-                shortests.insert(neigh, neigh_path);
-                queue.push_back(neigh);
+    for flag in flags {
+        match flag.split_once('=') {
+            Some(("--tiles", tiles)) => {
+                let (horizontal, vertical) = tiles.split_once('x').expect("Tiles are WxH");
+                tiling.horizontal = horizontal.parse().unwrap();
+                tiling.vertical = vertical.parse().unwrap();
             }
+            Some(("--wrap", rule)) => tiling.wrap = rule.parse().unwrap(),
+            None if flag.as_str() == "--diagonal" => diagonal = true,
+            _ => panic!("Unknown option: {}", flag),
         }
     }
 
-    shortests.get(&(sizing.area - 1)).copied().unwrap()
+    (tiling, diagonal)
 }
 
-fn inflate_5(cavern: Vec<u32>, sizing: CavernSize) -> (Vec<u32>, CavernSize) {
-    let horizontal: Vec<u32> = cavern[..]
-        .chunks(sizing.dimension)
-        .flat_map(|chunk| (0..5).flat_map(|i| chunk.iter().map(move |c| wrap_9(c + i))))
-        .collect();
-
-    let vertical = (0..5)
-        .flat_map(|i| horizontal.iter().map(move |c| wrap_9(c + i)))
-        .collect();
-
-    let new_dim = sizing.dimension * 5;
-
-    (
-        vertical,
-        CavernSize {
-            dimension: new_dim,
-            area: new_dim * new_dim,
-        },
-    )
-}
+// the widest line sets the width of the cavern, every line has to match it
+fn parse(input: String, tiling: Tiling, diagonal: bool) -> Result<Cavern, ScanError> {
+    let lines: Vec<&str> = input.trim().lines().map(str::trim).collect();
+    let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
 
-fn wrap_9(val: u32) -> u32 {
-    if val > 9 {
-        val - 9
-    } else {
-        val
+    if width == 0 {
+        return Err(ScanError::Empty);
     }
-}
-
-fn parse(input: String) -> (Vec<u32>, CavernSize) {
-    let &dimension = input
-        .trim()
-        .lines()
-        .take(1)
-        .map(|l| l.len())
-        .collect::<Vec<usize>>()
-        .first()
-        .unwrap();
-
-    let size = CavernSize {
-        dimension,
-        area: dimension * dimension,
-    };
-
-    let cavern = input
-        .trim()
-        .lines()
-        .flat_map(|l| l.trim().chars())
-        .map(|c| c.to_digit(10).unwrap())
-        .collect();
-
-    (cavern, size)
-}
 
-fn get_neighbor_positions(position: usize, cavern: &CavernSize) -> Vec<usize> {
-    let mut neighs = Vec::with_capacity(4);
-
-    if position >= cavern.dimension {
-        neighs.push(position - cavern.dimension);
+    if tiling.horizontal == 0 || tiling.vertical == 0 {
+        return Err(ScanError::Tiling {
+            horizontal: tiling.horizontal,
+            vertical: tiling.vertical,
+        });
     }
 
-    if position % cavern.dimension != 0 {
-        neighs.push(position - 1);
-    }
+    let mut risks = Vec::with_capacity(width * lines.len());
 
-    if position % cavern.dimension != (cavern.dimension - 1) {
-        neighs.push(position + 1);
-    }
+    for (y, line) in lines.iter().enumerate() {
+        if line.chars().count() != width {
+            return Err(ScanError::Width {
+                y,
+                found: line.chars().count(),
+                width,
+            });
+        }
 
-    if position < cavern.area - cavern.dimension {
-        neighs.push(position + cavern.dimension);
+        for (x, c) in line.chars().enumerate() {
+            risks.push(c.to_digit(10).ok_or(ScanError::Risk { x, y, c })?);
+        }
     }
 
-    neighs
+    Ok(Cavern::new(width, risks, tiling, diagonal))
 }
 
-struct CavernSize {
-    dimension: usize,
-    area: usize,
+#[derive(Debug, PartialEq, Eq)]
+enum ScanError {
+    Empty,
+    Risk {
+        x: usize,
+        y: usize,
+        c: char,
+    },
+    Width {
+        y: usize,
+        found: usize,
+        width: usize,
+    },
+    Tiling {
+        horizontal: usize,
+        vertical: usize,
+    },
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScanError::Empty => write!(f, "The scan shows no cavern"),
+            ScanError::Risk { x, y, c } => write!(f, "Risk '{}' at {},{} is not a digit", c, x, y),
+            ScanError::Width { y, found, width } => write!(
+                f,
+                "Scan line {} is {} wide, the cavern is {}",
+                y, found, width
+            ),
+            ScanError::Tiling {
+                horizontal,
+                vertical,
+            } => write!(
+                f,
+                "Tiling {}x{} leaves no cavern to search",
+                horizontal, vertical
+            ),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::cavern::WrapRule;
     use crate::*;
 
     const TEST_CASE: &str = "1163751742
@@ -163,4 +184,154 @@ mod tests {
     fn adv_check() {
         assert_eq!("315", process_data_adv(TEST_CASE.to_string()));
     }
+
+    #[test]
+    fn route_check() {
+        let cavern = parse(TEST_CASE.to_string(), Tiling::default(), false).unwrap();
+        let dijkstra = find_route(&cavern, Strategy::Dijkstra).unwrap();
+        let astar = find_route(&cavern, Strategy::AStar).unwrap();
+
+        assert_eq!(40, dijkstra.risk);
+        assert_eq!(40, astar.risk);
+        assert_eq!(
+            dijkstra.risk,
+            dijkstra.path[1..]
+                .iter()
+                .map(|p| cavern.risk(*p))
+                .sum::<u32>()
+        );
+        assert_eq!(Some(&0), dijkstra.path.first());
+        assert_eq!(Some(&99), astar.path.last());
+
+        let expected = "1.........
+1.........
+2136511...
+......15..
+.......11.
+........3.
+........2.
+........3.
+........21
+.........1";
+
+        assert_eq!(expected, search::render_route(&cavern, &dijkstra));
+    }
+
+    #[test]
+    fn astar_check() {
+        let tiling = Tiling {
+            horizontal: 5,
+            vertical: 5,
+            ..Tiling::default()
+        };
+        let cavern = parse(TEST_CASE.to_string(), tiling, false).unwrap();
+
+        assert_eq!(
+            Some(315),
+            find_route(&cavern, Strategy::AStar).map(|r| r.risk)
+        );
+        assert_eq!(Ok(Strategy::AStar), "astar".parse::<Strategy>());
+        assert!("bfs".parse::<Strategy>().is_err());
+    }
+
+    #[test]
+    fn tiling_check() {
+        let tiling = Tiling {
+            horizontal: 3,
+            vertical: 2,
+            ..Tiling::default()
+        };
+        let cavern = parse("89\n12".to_string(), tiling, false).unwrap();
+
+        assert_eq!((6, 4), (cavern.width(), cavern.height()));
+        // first row: 8 9 | 9 1 | 1 2, the second tile row starts one higher
+        let first_row: Vec<u32> = (0..6).map(|x| cavern.risk(x)).collect();
+        assert_eq!(vec![8, 9, 9, 1, 1, 2], first_row);
+        assert_eq!(9, cavern.risk(2 * 6));
+        assert_eq!(5, cavern.risk(3 * 6 + 5));
+        assert_eq!(1, cavern.min_risk());
+
+        let saturating = Tiling {
+            horizontal: 2,
+            vertical: 1,
+            wrap: WrapRule::Saturate(9),
+        };
+        let saturated = Cavern::new(2, vec![8, 9], saturating, false);
+        assert_eq!(
+            vec![8, 9, 9, 9],
+            (0..4).map(|x| saturated.risk(x)).collect::<Vec<u32>>()
+        );
+
+        assert_eq!(Ok(WrapRule::Unbounded), "none".parse::<WrapRule>());
+        assert_eq!(Ok(WrapRule::Cycle(3)), "cycle:3".parse::<WrapRule>());
+        for invalid in ["cycle:x", "cycle:0", "saturate:0", "wrap:9"] {
+            assert!(invalid.parse::<WrapRule>().is_err());
+        }
+    }
+
+    #[test]
+    fn rectangular_check() {
+        let cavern = parse("1911\n1919\n1119".to_string(), Tiling::default(), false).unwrap();
+        let route = find_route(&cavern, Strategy::AStar).unwrap();
+
+        assert_eq!((4, 3), (cavern.width(), cavern.height()));
+        assert_eq!(13, route.risk);
+        assert_eq!("1...\n1...\n1119", search::render_route(&cavern, &route));
+    }
+
+    #[test]
+    fn diagonal_check() {
+        let input = "199\n919\n991";
+        let straight = parse(input.to_string(), Tiling::default(), false).unwrap();
+        let diagonal = parse(input.to_string(), Tiling::default(), true).unwrap();
+
+        assert_eq!(
+            Some(20),
+            find_route(&straight, Strategy::Dijkstra).map(|r| r.risk)
+        );
+        assert_eq!(
+            Some(2),
+            find_route(&diagonal, Strategy::Dijkstra).map(|r| r.risk)
+        );
+        assert_eq!(
+            Some(2),
+            find_route(&diagonal, Strategy::AStar).map(|r| r.risk)
+        );
+        assert_eq!(
+            vec![0, 4, 8],
+            find_route(&diagonal, Strategy::AStar).unwrap().path
+        );
+    }
+
+    #[test]
+    fn invalid_scan_check() {
+        let parse = |input: &str| parse(input.to_string(), Tiling::default(), false).map(|_| ());
+
+        assert_eq!(Err(ScanError::Empty), parse("\n"));
+        assert_eq!(Err(ScanError::Risk { x: 1, y: 1, c: '?' }), parse("12\n3?"));
+        assert_eq!(
+            Err(ScanError::Width {
+                y: 0,
+                found: 2,
+                width: 3
+            }),
+            parse("12\n345")
+        );
+
+        for (horizontal, vertical) in [(0, 5), (5, 0)] {
+            let tiling = Tiling {
+                horizontal,
+                vertical,
+                ..Tiling::default()
+            };
+
+            assert_eq!(
+                Err(ScanError::Tiling {
+                    horizontal,
+                    vertical
+                }),
+                crate::parse("12\n34".to_string(), tiling, false).map(|_| ())
+            );
+        }
+    }
 }
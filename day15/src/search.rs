@@ -0,0 +1,114 @@
+use std::{cmp::Reverse, collections::BinaryHeap, str::FromStr};
+
+use crate::cavern::Cavern;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    Dijkstra,
+    AStar,
+}
+
+impl FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dijkstra" => Ok(Strategy::Dijkstra),
+            "astar" => Ok(Strategy::AStar),
+            _ => Err(format!("Unknown strategy: {}", s)),
+        }
+    }
+}
+
+// the path starts in the top left and ends in the bottom right cell,
+// the risk of the starting cell is not counted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    pub risk: u32,
+    pub path: Vec<usize>,
+}
+
+pub fn find_route(cavern: &Cavern, strategy: Strategy) -> Option<Route> {
+    let target = cavern.area().checked_sub(1)?;
+    let min_risk = cavern.min_risk();
+
+    // no step costs less than the lowest risk, so a straight walk
+    // at that risk never overestimates
+    let heuristic = |position: usize| match strategy {
+        Strategy::Dijkstra => 0,
+        Strategy::AStar => get_distance(position, target, cavern) * min_risk,
+    };
+
+    let mut risks = vec![u32::MAX; cavern.area()];
+    let mut previous = vec![None; cavern.area()];
+    let mut queue = BinaryHeap::new();
+
+    risks[0] = 0;
+    queue.push(Reverse((heuristic(0), 0, 0usize)));
+
+    while let Some(Reverse((_, risk, position))) = queue.pop() {
+        if position == target {
+            return Some(Route {
+                risk,
+                path: get_path(&previous, target),
+            });
+        }
+
+        if risk > risks[position] {
+            continue;
+        }
+
+        for neigh in cavern.neighbors(position) {
+            let neigh_risk = risk + cavern.risk(neigh);
+
+            if neigh_risk < risks[neigh] {
+                risks[neigh] = neigh_risk;
+                previous[neigh] = Some(position);
+                queue.push(Reverse((neigh_risk + heuristic(neigh), neigh_risk, neigh)));
+            }
+        }
+    }
+
+    None
+}
+
+// cells off the path are blanked out
+pub fn render_route(cavern: &Cavern, route: &Route) -> String {
+    let mut cells: Vec<char> = vec!['.'; cavern.area()];
+
+    for &position in route.path.iter() {
+        cells[position] = char::from_digit(cavern.risk(position), 10).unwrap_or('#');
+    }
+
+    cells
+        .chunks(cavern.width())
+        .map(|row| row.iter().collect::<String>())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn get_path(previous: &[Option<usize>], target: usize) -> Vec<usize> {
+    let mut path = vec![target];
+
+    while let Some(p) = previous[*path.last().unwrap()] {
+        path.push(p);
+    }
+
+    path.reverse();
+
+    path
+}
+
+// with diagonal moves the fewest steps is the larger of the two distances
+fn get_distance(position: usize, target: usize, cavern: &Cavern) -> u32 {
+    let width = cavern.width();
+    let (x, y) = (position % width, position / width);
+    let (tx, ty) = (target % width, target / width);
+    let (dx, dy) = (x.abs_diff(tx), y.abs_diff(ty));
+
+    if cavern.is_diagonal() {
+        dx.max(dy) as u32
+    } else {
+        (dx + dy) as u32
+    }
+}
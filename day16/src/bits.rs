@@ -0,0 +1,234 @@
+use std::fmt;
+
+use crate::packet::{Packet, PacketType};
+
+// positions are bit offsets into the transmission, except for bad hex
+// digits which are reported by their character offset
+#[derive(Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    InvalidHex {
+        offset: usize,
+        found: char,
+    },
+    Truncated {
+        position: usize,
+        needed: usize,
+        available: usize,
+    },
+    BadLength {
+        position: usize,
+        expected: usize,
+        found: usize,
+    },
+    LiteralOverflow {
+        position: usize,
+    },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::InvalidHex { offset, found } => {
+                write!(f, "Invalid hex digit '{}' at offset {}", found, offset)
+            }
+            DecodeError::Truncated {
+                position,
+                needed,
+                available,
+            } => write!(
+                f,
+                "Transmission truncated at bit {}, needed {} bits but only {} left",
+                position, needed, available
+            ),
+            DecodeError::BadLength {
+                position,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Sub-packets at bit {} span {} bits, expected {}",
+                position, found, expected
+            ),
+            DecodeError::LiteralOverflow { position } => {
+                write!(
+                    f,
+                    "Literal value at bit {} does not fit in 64 bits",
+                    position
+                )
+            }
+        }
+    }
+}
+
+// reads bits straight out of the hex digits, most significant first
+#[derive(Clone)]
+pub struct BitReader<'a> {
+    hex: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(hex: &'a str) -> Self {
+        BitReader {
+            hex: hex.as_bytes(),
+            position: 0,
+        }
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn len(&self) -> usize {
+        self.hex.len() * 4
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.len() - self.position
+    }
+
+    pub fn read(&mut self, count: usize) -> Result<u64, DecodeError> {
+        assert!(count <= 64, "Cannot read more than 64 bits at once");
+
+        if count > self.remaining() {
+            return Err(DecodeError::Truncated {
+                position: self.position,
+                needed: count,
+                available: self.remaining(),
+            });
+        }
+
+        let mut value = 0u64;
+        let mut rest = count;
+
+        while rest > 0 {
+            let nibble = self.get_nibble(self.position / 4)?;
+            let offset = self.position % 4;
+            let take = (4 - offset).min(rest);
+            let bits = (nibble >> (4 - offset - take)) & ((1 << take) - 1);
+
+            value = (value << take) | bits as u64;
+            rest -= take;
+            self.position += take;
+        }
+
+        Ok(value)
+    }
+
+    // hex digits are only padded with zeros at the end of a transmission
+    pub fn is_padding(&self) -> Result<bool, DecodeError> {
+        let mut probe = self.clone();
+
+        while probe.remaining() > 0 {
+            if probe.read(probe.remaining().min(64))? != 0 {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    fn get_nibble(&self, index: usize) -> Result<u8, DecodeError> {
+        let c = self.hex[index] as char;
+
+        c.to_digit(16)
+            .map(|d| d as u8)
+            .ok_or(DecodeError::InvalidHex {
+                offset: index,
+                found: c,
+            })
+    }
+}
+
+pub fn decode(input: &str) -> Result<Vec<Packet>, DecodeError> {
+    let mut reader = BitReader::new(input.trim());
+    let mut packets = Vec::new();
+
+    while !reader.is_padding()? {
+        packets.push(decode_packet(&mut reader)?);
+    }
+
+    Ok(packets)
+}
+
+fn decode_packet(reader: &mut BitReader) -> Result<Packet, DecodeError> {
+    let version = reader.read(3)? as u32;
+
+    // BITS assigns all eight 3 bit type ids, so no type can be unknown
+    let packet_type = match reader.read(3)? {
+        4 => {
+            return Ok(Packet {
+                version,
+                packet_type: PacketType::LiteralValue(decode_literal(reader)?),
+                children: vec![],
+            })
+        }
+        0 => PacketType::SumOperator,
+        1 => PacketType::ProductOperator,
+        2 => PacketType::MinimumOperator,
+        3 => PacketType::MaximumOperator,
+        5 => PacketType::GreaterThanOperator,
+        6 => PacketType::LessThanOperator,
+        _ => PacketType::EqualToOperator,
+    };
+
+    Ok(Packet {
+        version,
+        packet_type,
+        children: decode_children(reader)?,
+    })
+}
+
+fn decode_literal(reader: &mut BitReader) -> Result<u64, DecodeError> {
+    let position = reader.position();
+    let mut value = 0u64;
+
+    loop {
+        let group = reader.read(5)?;
+
+        if value >> 60 != 0 {
+            return Err(DecodeError::LiteralOverflow { position });
+        }
+
+        value = (value << 4) | (group & 0xF);
+
+        if group & 0x10 == 0 {
+            return Ok(value);
+        }
+    }
+}
+
+fn decode_children(reader: &mut BitReader) -> Result<Vec<Packet>, DecodeError> {
+    if reader.read(1)? == 1 {
+        let count = reader.read(11)?;
+
+        return (0..count).map(|_| decode_packet(reader)).collect();
+    }
+
+    let length = reader.read(15)? as usize;
+    let start = reader.position();
+
+    if length > reader.remaining() {
+        return Err(DecodeError::Truncated {
+            position: start,
+            needed: length,
+            available: reader.remaining(),
+        });
+    }
+
+    let mut children = Vec::new();
+
+    while reader.position() < start + length {
+        children.push(decode_packet(reader)?);
+    }
+
+    if reader.position() != start + length {
+        return Err(DecodeError::BadLength {
+            position: start,
+            expected: length,
+            found: reader.position() - start,
+        });
+    }
+
+    Ok(children)
+}
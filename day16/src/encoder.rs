@@ -0,0 +1,167 @@
+use std::{fmt, str::FromStr};
+
+use crate::packet::{Packet, PacketType};
+
+const MAX_BITS: usize = (1 << 15) - 1;
+const MAX_COUNT: usize = (1 << 11) - 1;
+
+// how operators announce their sub-packets, when the preferred way
+// cannot hold the size the other one is used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LengthType {
+    Bits,
+    Count,
+}
+
+impl FromStr for LengthType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bits" => Ok(LengthType::Bits),
+            "count" => Ok(LengthType::Count),
+            _ => Err(format!("Unknown length type: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum EncodeError {
+    Version(u32),
+    LiteralChildren(usize),
+    TooLarge { bits: usize, count: usize },
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EncodeError::Version(version) => {
+                write!(f, "Version {} does not fit in 3 bits", version)
+            }
+            EncodeError::LiteralChildren(count) => {
+                write!(f, "Literal value with {} sub-packets", count)
+            }
+            EncodeError::TooLarge { bits, count } => write!(
+                f,
+                "{} sub-packets spanning {} bits fit neither length type",
+                count, bits
+            ),
+        }
+    }
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bits: Vec<bool>,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u64, count: usize) {
+        self.bits
+            .extend((0..count).rev().map(|i| (value >> i) & 1 == 1));
+    }
+
+    // zero padded to whole bytes like the transmissions we receive
+    fn to_hex(&self) -> String {
+        let mut bits = self.bits.clone();
+        bits.resize(bits.len().div_ceil(8) * 8, false);
+
+        bits.chunks(4)
+            .map(|nibble| nibble.iter().fold(0, |acc, &b| (acc << 1) | b as u32))
+            .map(|n| char::from_digit(n, 16).unwrap().to_ascii_uppercase())
+            .collect()
+    }
+}
+
+pub fn encode(packet: &Packet, preferred: LengthType) -> Result<String, EncodeError> {
+    let mut writer = BitWriter::default();
+
+    write_packet(&mut writer, packet, preferred)?;
+
+    Ok(writer.to_hex())
+}
+
+fn write_packet(
+    writer: &mut BitWriter,
+    packet: &Packet,
+    preferred: LengthType,
+) -> Result<(), EncodeError> {
+    if packet.version > 7 {
+        return Err(EncodeError::Version(packet.version));
+    }
+
+    writer.write(packet.version as u64, 3);
+    writer.write(get_type_id(&packet.packet_type), 3);
+
+    match packet.packet_type {
+        PacketType::LiteralValue(value) if packet.children.is_empty() => {
+            write_literal(writer, value);
+            Ok(())
+        }
+        PacketType::LiteralValue(_) => Err(EncodeError::LiteralChildren(packet.children.len())),
+        _ => write_children(writer, &packet.children, preferred),
+    }
+}
+
+// groups of 4 bits, each but the last one flagged as continued
+fn write_literal(writer: &mut BitWriter, value: u64) {
+    let groups = ((64 - value.leading_zeros() as usize).div_ceil(4)).max(1);
+
+    for i in (0..groups).rev() {
+        writer.write((i > 0) as u64, 1);
+        writer.write(value >> (i * 4), 4);
+    }
+}
+
+fn write_children(
+    writer: &mut BitWriter,
+    children: &[Packet],
+    preferred: LengthType,
+) -> Result<(), EncodeError> {
+    let mut body = BitWriter::default();
+
+    for child in children {
+        write_packet(&mut body, child, preferred)?;
+    }
+
+    let (bits, count) = (body.bits.len(), children.len());
+
+    // without sub-packets a bit length of zero would leave nothing but
+    // zeros, which reads as padding at the end of a transmission
+    let length_type = match preferred {
+        _ if count == 0 => LengthType::Count,
+        LengthType::Bits if bits <= MAX_BITS => LengthType::Bits,
+        LengthType::Count if count <= MAX_COUNT => LengthType::Count,
+        _ if bits <= MAX_BITS => LengthType::Bits,
+        _ if count <= MAX_COUNT => LengthType::Count,
+        _ => return Err(EncodeError::TooLarge { bits, count }),
+    };
+
+    match length_type {
+        LengthType::Bits => {
+            writer.write(0, 1);
+            writer.write(bits as u64, 15);
+        }
+        LengthType::Count => {
+            writer.write(1, 1);
+            writer.write(count as u64, 11);
+        }
+    }
+
+    writer.bits.extend(body.bits);
+
+    Ok(())
+}
+
+fn get_type_id(packet_type: &PacketType) -> u64 {
+    match packet_type {
+        PacketType::SumOperator => 0,
+        PacketType::ProductOperator => 1,
+        PacketType::MinimumOperator => 2,
+        PacketType::MaximumOperator => 3,
+        PacketType::LiteralValue(_) => 4,
+        PacketType::GreaterThanOperator => 5,
+        PacketType::LessThanOperator => 6,
+        PacketType::EqualToOperator => 7,
+    }
+}
@@ -1,15 +1,47 @@
-#![feature(is_sorted)]
+#![feature(test)]
 
+extern crate test;
+
+mod bits;
+mod encoder;
 mod packet;
 
-use std::fs;
+use std::{env, fs};
 
+use bits::decode;
+use encoder::{encode, LengthType};
 use packet::Packet;
 
 // TODO: use 'nom' crate instead of custom parser?
 fn main() {
     let input = fs::read_to_string("./_data/input.txt").expect("oh noes");
 
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    // strings: evaluate with the string based parser for comparison
+    // encode [bits|count]: write the transmission back out
+    match args.first().map(|a| a.as_str()) {
+        Some("strings") => {
+            let value = parse(input.clone())
+                .iter()
+                .map(|p| p.evaluate())
+                .sum::<u64>();
+
+            println!("Custom result is: {}", value);
+        }
+        Some("encode") => {
+            let preferred = args
+                .get(1)
+                .map(|a| a.parse::<LengthType>().unwrap())
+                .unwrap_or(LengthType::Count);
+
+            for packet in get_packets(&input) {
+                println!("{}", encode(&packet, preferred).unwrap());
+            }
+        }
+        _ => (),
+    }
+
     let output = process_data(input.clone());
     let adv_output = process_data_adv(input);
 
@@ -18,7 +50,7 @@ fn main() {
 }
 
 fn process_data(input: String) -> String {
-    parse(input)
+    get_packets(&input)
         .iter()
         .map(|p| p.version_sum())
         .sum::<u32>()
@@ -26,13 +58,17 @@ fn process_data(input: String) -> String {
 }
 
 fn process_data_adv(input: String) -> String {
-    parse(input)
+    get_packets(&input)
         .iter()
         .map(|p| p.evaluate())
         .sum::<u64>()
         .to_string()
 }
 
+fn get_packets(input: &str) -> Vec<Packet> {
+    decode(input).unwrap_or_else(|e| panic!("Invalid transmission: {}", e))
+}
+
 fn parse(input: String) -> Vec<Packet> {
     Packet::parse_from(
         input
@@ -70,8 +106,13 @@ fn hex_to_binary(input: char) -> String {
 
 #[cfg(test)]
 mod tests {
+    use crate::bits::{BitReader, DecodeError};
+    use crate::encoder::EncodeError;
+    use crate::packet::PacketType;
     use crate::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
     use rstest::rstest;
+    use test::Bencher;
 
     #[rstest]
     #[case("EE00D40C823060", "14")]
@@ -95,4 +136,163 @@ mod tests {
     fn adv_check(#[case] input: &str, #[case] expected: &str) {
         assert_eq!(expected, process_data_adv(input.to_string()));
     }
+
+    #[rstest]
+    #[case("D2FE28")]
+    #[case("38006F45291200")]
+    #[case("EE00D40C823060")]
+    #[case("9C0141080250320F1802104A08")]
+    #[case("A0016C880162017C3686B18A3D4780")]
+    fn decode_check(#[case] input: &str) {
+        assert_eq!(parse(input.to_string()), decode(input).unwrap());
+    }
+
+    #[test]
+    fn reader_check() {
+        let mut reader = BitReader::new("D2FE28");
+
+        assert_eq!(Ok(6), reader.read(3));
+        assert_eq!(Ok(4), reader.read(3));
+        assert_eq!(Ok(0b10111), reader.read(5));
+        assert_eq!(11, reader.position());
+        assert_eq!(Ok(0xFE28 & 0x1FFF), reader.read(13));
+        assert_eq!(0, reader.remaining());
+        assert_eq!(Ok(true), reader.is_padding());
+    }
+
+    #[rstest]
+    #[case(
+        "D2FE2",
+        DecodeError::Truncated {
+            position: 16,
+            needed: 5,
+            available: 4
+        }
+    )]
+    #[case("D2FG28", DecodeError::InvalidHex { offset: 3, found: 'G' })]
+    // length type 0 claiming 10 bits around a literal of 11 bits
+    #[case(
+        "20002B450",
+        DecodeError::BadLength {
+            position: 22,
+            expected: 10,
+            found: 11
+        }
+    )]
+    // length type 0 claiming more bits than are left
+    #[case(
+        "200190",
+        DecodeError::Truncated {
+            position: 22,
+            needed: 100,
+            available: 2
+        }
+    )]
+    #[case("D2FFFFFFFFFFFFFFFFFFFFFFFF", DecodeError::LiteralOverflow { position: 6 })]
+    fn decode_error_check(#[case] input: &str, #[case] expected: DecodeError) {
+        assert_eq!(Err(expected), decode(input));
+    }
+
+    #[rstest]
+    #[case("D2FE28", LengthType::Count)]
+    #[case("38006F45291200", LengthType::Bits)]
+    #[case("EE00D40C823060", LengthType::Count)]
+    #[case("C200B40A82", LengthType::Count)]
+    #[case("04005AC33890", LengthType::Bits)]
+    fn encode_check(#[case] input: &str, #[case] preferred: LengthType) {
+        let packets = decode(input).unwrap();
+
+        assert_eq!(Ok(input.to_string()), encode(&packets[0], preferred));
+    }
+
+    fn get_random_packet(rng: &mut StdRng, depth: u32) -> Packet {
+        let version = rng.gen_range(0..8);
+
+        if depth == 0 || rng.gen_bool(0.3) {
+            return Packet {
+                version,
+                packet_type: PacketType::LiteralValue(rng.gen::<u64>() >> rng.gen_range(0..64)),
+                children: vec![],
+            };
+        }
+
+        let packet_type = match rng.gen_range(0..7) {
+            0 => PacketType::SumOperator,
+            1 => PacketType::ProductOperator,
+            2 => PacketType::MinimumOperator,
+            3 => PacketType::MaximumOperator,
+            4 => PacketType::GreaterThanOperator,
+            5 => PacketType::LessThanOperator,
+            _ => PacketType::EqualToOperator,
+        };
+
+        Packet {
+            version,
+            packet_type,
+            children: (0..rng.gen_range(0..4))
+                .map(|_| get_random_packet(rng, depth - 1))
+                .collect(),
+        }
+    }
+
+    #[rstest]
+    #[case(LengthType::Bits)]
+    #[case(LengthType::Count)]
+    fn round_trip_check(#[case] preferred: LengthType) {
+        let mut rng = StdRng::seed_from_u64(16);
+
+        for _ in 0..200 {
+            let packet = get_random_packet(&mut rng, 4);
+            let hex = encode(&packet, preferred).unwrap();
+
+            assert_eq!(Ok(vec![packet.clone()]), decode(&hex));
+            assert_eq!(vec![packet], parse(hex));
+        }
+    }
+
+    #[test]
+    fn encode_error_check() {
+        let literal = |version, children| Packet {
+            version,
+            packet_type: PacketType::LiteralValue(1),
+            children,
+        };
+
+        assert_eq!(
+            Err(EncodeError::Version(8)),
+            encode(&literal(8, vec![]), LengthType::Bits)
+        );
+        assert_eq!(
+            Err(EncodeError::LiteralChildren(1)),
+            encode(&literal(0, vec![literal(0, vec![])]), LengthType::Bits)
+        );
+
+        // 3000 literals of 11 bits are too many to count and too long to measure
+        let huge = Packet {
+            version: 0,
+            packet_type: PacketType::SumOperator,
+            children: vec![literal(0, vec![]); 3000],
+        };
+        assert_eq!(
+            Err(EncodeError::TooLarge {
+                bits: 33000,
+                count: 3000
+            }),
+            encode(&huge, LengthType::Count)
+        );
+    }
+
+    #[bench]
+    fn string_parser_bench(b: &mut Bencher) {
+        let input = fs::read_to_string("./_data/input.txt").expect("oh noes");
+
+        b.iter(|| parse(input.clone()));
+    }
+
+    #[bench]
+    fn bit_decoder_bench(b: &mut Bencher) {
+        let input = fs::read_to_string("./_data/input.txt").expect("oh noes");
+
+        b.iter(|| decode(&input).unwrap());
+    }
 }
@@ -1,4 +1,4 @@
-﻿#[derive(Debug)]
+﻿#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PacketType {
     LiteralValue(u64),
     SumOperator,
@@ -10,11 +10,11 @@ pub enum PacketType {
     EqualToOperator,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Packet {
-    version: u32,
-    packet_type: PacketType,
-    children: Vec<Packet>,
+    pub version: u32,
+    pub packet_type: PacketType,
+    pub children: Vec<Packet>,
 }
 
 impl Packet {